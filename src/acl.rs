@@ -0,0 +1,219 @@
+use std::{fs, io, net::IpAddr, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::userdata::data_path;
+
+const LAN: &[&str] = &[
+    "0.0.0.0/8",
+    "10.0.0.0/8",
+    "100.64.0.0/10",
+    "127.0.0.0/8",
+    "169.254.0.0/16",
+    "172.16.0.0/12",
+    "192.168.0.0/16",
+    "224.0.0.0/4",
+    "::1/128",
+    "fc00::/7",
+    "fe80::/10",
+    "localhost",
+    "local",
+];
+
+const CHINA_IP_LIST: &str =
+    "https://raw.githubusercontent.com/17mon/china_ip_list/master/china_ip_list.txt";
+
+/// A named set of routing rules, stored as `acl/<name>.json` and turned into
+/// a sslocal ACL file when sslocal starts.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AclRuleSet {
+    pub name: String,
+    /// Proxy everything except `bypass`, or bypass everything except `proxy`.
+    pub proxy_all: bool,
+    /// IPs, CIDRs or domains, a domain matches its subdomains too.
+    pub bypass: Vec<String>,
+    pub proxy: Vec<String>,
+    /// A plain text list of bypassed IPs/CIDRs downloaded on update, cached as
+    /// `acl/<name>.source`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_url: Option<String>,
+}
+
+pub struct LoadedRuleSets {
+    pub rule_sets: Vec<AclRuleSet>,
+    /// Names of the files that couldn't be read, with the reason.
+    pub skipped: Vec<(String, String)>,
+}
+
+impl AclRuleSet {
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        Self {
+            name: name.into(),
+            proxy_all: true,
+            bypass: Vec::new(),
+            proxy: Vec::new(),
+            source_url: None,
+        }
+    }
+
+    fn presets() -> Vec<Self> {
+        let lan: Vec<String> = LAN.iter().map(|s| s.to_string()).collect();
+        let mut china = lan.clone();
+        china.push("cn".to_string());
+        vec![
+            Self {
+                bypass: lan,
+                ..Self::new("bypass-lan")
+            },
+            Self {
+                bypass: china,
+                source_url: Some(CHINA_IP_LIST.to_string()),
+                ..Self::new("bypass-china")
+            },
+        ]
+    }
+
+    fn path(name: &str, extension: &str) -> io::Result<PathBuf> {
+        Ok(data_path("acl")?.join(format!("{}.{}", name, extension)))
+    }
+
+    /// All rule sets by name, the presets are written on first use. Files that
+    /// can't be read are skipped.
+    pub fn load_all() -> io::Result<LoadedRuleSets> {
+        let dir = data_path("acl")?;
+        if !dir.exists() {
+            fs::create_dir_all(&dir)?;
+            for preset in Self::presets() {
+                preset.save()?;
+            }
+        }
+        let mut rule_sets = Vec::new();
+        let mut skipped = Vec::new();
+        for entry in dir.read_dir()? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|e| e == "json") {
+                let name = path
+                    .file_stem()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_string();
+                match fs::read(&path)
+                    .and_then(|content| Ok(serde_json::from_slice::<Self>(&content)?))
+                {
+                    Ok(rule_set) => rule_sets.push(rule_set),
+                    Err(err) => skipped.push((name, err.to_string())),
+                }
+            }
+        }
+        rule_sets.sort_by(|a, b| a.name.cmp(&b.name));
+        skipped.sort();
+        Ok(LoadedRuleSets { rule_sets, skipped })
+    }
+
+    pub fn load(name: &str) -> io::Result<Self> {
+        let content = fs::read(Self::path(name, "json")?)?;
+        Ok(serde_json::from_slice(&content)?)
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        fs::create_dir_all(data_path("acl")?)?;
+        fs::write(Self::path(&self.name, "json")?, serde_json::to_vec(self)?)
+    }
+
+    pub fn delete(&self) -> io::Result<()> {
+        for extension in ["json", "source", "acl"] {
+            let path = Self::path(&self.name, extension)?;
+            if path.exists() {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Names become file names, so only a safe subset is allowed.
+    pub fn validate_name(name: &str) -> anyhow::Result<()> {
+        if name.is_empty() {
+            anyhow::bail!("name is empty");
+        }
+        if !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            anyhow::bail!("name may only contain letters, digits, '-' and '_'");
+        }
+        Ok(())
+    }
+
+    /// Downloads `source_url`, optionally through a proxy, and returns the
+    /// number of rules it contains.
+    pub fn update_source(&self, proxy: Option<&str>) -> anyhow::Result<usize> {
+        let Some(url) = &self.source_url else {
+            anyhow::bail!("'{}' has no source url", self.name);
+        };
+        let mut builder = ureq::AgentBuilder::new();
+        if let Some(proxy) = proxy {
+            builder = builder.proxy(ureq::Proxy::new(proxy)?);
+        }
+        let content = builder.build().get(url).call()?.into_string()?;
+        let rules = content
+            .lines()
+            .filter(|line| Self::is_ip_rule(line.trim()))
+            .count();
+        if rules == 0 {
+            anyhow::bail!("no IP or CIDR found in '{}'", url);
+        }
+        fs::write(Self::path(&self.name, "source")?, content)?;
+        Ok(rules)
+    }
+
+    pub fn has_source(&self) -> bool {
+        Self::path(&self.name, "source").is_ok_and(|path| path.exists())
+    }
+
+    fn is_ip_rule(rule: &str) -> bool {
+        let ip = rule.split_once('/').map_or(rule, |(ip, _)| ip);
+        ip.parse::<IpAddr>().is_ok()
+    }
+
+    /// IPs and CIDRs are used as they are, regular expressions (starting with
+    /// `^`) too, a plain domain becomes one matching it and its subdomains.
+    fn acl_rule(rule: &str) -> String {
+        if Self::is_ip_rule(rule) || rule.starts_with('^') {
+            rule.to_string()
+        } else {
+            format!("(^|\\.){}$", rule.replace('.', "\\."))
+        }
+    }
+
+    /// Writes `acl/<name>.acl` for sslocal's `--acl`.
+    pub fn generate(&self) -> io::Result<PathBuf> {
+        let mut content = String::new();
+        content.push_str(if self.proxy_all {
+            "[proxy_all]\n"
+        } else {
+            "[bypass_all]\n"
+        });
+        content.push_str("\n[bypass_list]\n");
+        for rule in &self.bypass {
+            content.push_str(&Self::acl_rule(rule));
+            content.push('\n');
+        }
+        if self.source_url.is_some() && self.has_source() {
+            let source = fs::read_to_string(Self::path(&self.name, "source")?)?;
+            for rule in source.lines().map(str::trim) {
+                if Self::is_ip_rule(rule) {
+                    content.push_str(rule);
+                    content.push('\n');
+                }
+            }
+        }
+        content.push_str("\n[proxy_list]\n");
+        for rule in &self.proxy {
+            content.push_str(&Self::acl_rule(rule));
+            content.push('\n');
+        }
+        let path = Self::path(&self.name, "acl")?;
+        fs::write(&path, content)?;
+        Ok(path)
+    }
+}
@@ -0,0 +1,428 @@
+use std::{
+    collections::VecDeque,
+    io::{ErrorKind, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    process::Child,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{channel, Receiver},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::{sslocal::SSLocal, userdata::SSServer};
+
+#[derive(Debug, Clone)]
+pub enum Latency {
+    Testing,
+    Ok(Duration),
+    Timeout,
+    /// The proxy accepted the request but the server closed the connection
+    /// without answering, usually a wrong password or cipher.
+    Rejected,
+    /// Short reason, small enough for a table cell.
+    Failed(String),
+}
+
+impl Latency {
+    pub fn as_duration(&self) -> Option<Duration> {
+        match self {
+            Latency::Ok(duration) => Some(*duration),
+            _ => None,
+        }
+    }
+
+    pub fn is_failure(&self) -> bool {
+        matches!(
+            self,
+            Latency::Timeout | Latency::Rejected | Latency::Failed(_)
+        )
+    }
+}
+
+pub fn tcp_ping(address: &str, timeout: Duration) -> Latency {
+    let Ok(Some(addr)) = address.to_socket_addrs().map(|mut addrs| addrs.next()) else {
+        return Latency::Failed("dns error".to_string());
+    };
+    let start = Instant::now();
+    match TcpStream::connect_timeout(&addr, timeout) {
+        Ok(_) => Latency::Ok(start.elapsed()),
+        Err(err) => io_error_latency(&err),
+    }
+}
+
+/// Sends `GET url` through the SOCKS5 proxy at `proxy` and times the whole
+/// exchange up to the response status line. Only `http://` urls are supported.
+pub fn http_probe(proxy: SocketAddr, url: &str, timeout: Duration) -> Latency {
+    let Some((host, port, path)) = parse_http_url(url) else {
+        return Latency::Failed("bad probe url".to_string());
+    };
+    let start = Instant::now();
+    let mut stream = match TcpStream::connect_timeout(&proxy, timeout) {
+        Ok(stream) => stream,
+        Err(err) => return io_error_latency(&err),
+    };
+    if let Err(err) = stream
+        .set_read_timeout(Some(timeout))
+        .and_then(|_| stream.set_write_timeout(Some(timeout)))
+    {
+        return io_error_latency(&err);
+    }
+
+    // greeting, no authentication
+    let mut reply = [0u8; 2];
+    if let Err(err) = stream
+        .write_all(&[5, 1, 0])
+        .and_then(|_| stream.read_exact(&mut reply))
+    {
+        return io_error_latency(&err);
+    }
+    if reply != [5, 0] {
+        return Latency::Failed("socks error".to_string());
+    }
+
+    // CONNECT by domain name
+    let mut request = vec![5, 1, 0, 3, host.len() as u8];
+    request.extend_from_slice(host.as_bytes());
+    request.extend_from_slice(&port.to_be_bytes());
+    let mut reply = [0u8; 4];
+    if let Err(err) = stream
+        .write_all(&request)
+        .and_then(|_| stream.read_exact(&mut reply))
+    {
+        return io_error_latency(&err);
+    }
+    if reply[1] != 0 {
+        return Latency::Failed(format!("socks error {}", reply[1]));
+    }
+    let bound_len = match reply[3] {
+        1 => 4 + 2,
+        4 => 16 + 2,
+        3 => {
+            let mut len = [0u8; 1];
+            if let Err(err) = stream.read_exact(&mut len) {
+                return io_error_latency(&err);
+            }
+            len[0] as usize + 2
+        }
+        _ => return Latency::Failed("socks error".to_string()),
+    };
+    let mut bound = vec![0u8; bound_len];
+    if let Err(err) = stream.read_exact(&mut bound) {
+        return io_error_latency(&err);
+    }
+
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: sstui\r\nConnection: close\r\n\r\n",
+        path, host
+    );
+    if let Err(err) = stream.write_all(request.as_bytes()) {
+        return io_error_latency(&err);
+    }
+    let mut response = [0u8; 12];
+    let mut n = 0;
+    while n < response.len() {
+        match stream.read(&mut response[n..]) {
+            Ok(0) => break,
+            Ok(len) => n += len,
+            Err(err) if n == 0 && is_reset(&err) => return Latency::Rejected,
+            Err(err) => return io_error_latency(&err),
+        }
+    }
+    match n {
+        0 => Latency::Rejected,
+        _ if response[..n].starts_with(b"HTTP/") => Latency::Ok(start.elapsed()),
+        _ => Latency::Failed("bad response".to_string()),
+    }
+}
+
+/// Starts a throwaway sslocal for `server` on a free port and probes `url`
+/// through it.
+pub fn real_ping(sslocal: &SSLocal, server: &SSServer, url: &str, timeout: Duration) -> Latency {
+    let port = match TcpListener::bind("127.0.0.1:0").and_then(|l| l.local_addr()) {
+        Ok(addr) => addr.port(),
+        Err(err) => return io_error_latency(&err),
+    };
+    let mut child = match sslocal.run_probe(server, port) {
+        Ok(child) => child,
+        Err(err) if err.kind() == ErrorKind::NotFound => {
+            return Latency::Failed("no plugin".to_string())
+        }
+        Err(err) => return io_error_latency(&err),
+    };
+    let proxy = SocketAddr::from(([127, 0, 0, 1], port));
+    let ready = wait_listening(&mut child, proxy, timeout);
+    let latency = if ready {
+        http_probe(proxy, url, timeout)
+    } else {
+        Latency::Failed("sslocal failed".to_string())
+    };
+    let _ = child.kill();
+    let _ = child.wait();
+    latency
+}
+
+/// Waits until sslocal accepts connections, false if it exited or timed out.
+fn wait_listening(child: &mut Child, addr: SocketAddr, timeout: Duration) -> bool {
+    let start = Instant::now();
+    while start.elapsed() < timeout {
+        if !matches!(child.try_wait(), Ok(None)) {
+            return false;
+        }
+        if TcpStream::connect_timeout(&addr, Duration::from_millis(100)).is_ok() {
+            return true;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    false
+}
+
+/// Splits `http://host[:port][/path]` into its parts.
+fn parse_http_url(url: &str) -> Option<(String, u16, String)> {
+    let rest = url.strip_prefix("http://")?;
+    let (authority, path) = match rest.find('/') {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, "/"),
+    };
+    let (host, port) = match authority.strip_prefix('[') {
+        Some(rest) => match rest.split_once("]:") {
+            Some((host, port)) => (host, port.parse().ok()?),
+            None => (rest.trim_end_matches(']'), 80),
+        },
+        None => match authority.rsplit_once(':') {
+            Some((host, port)) => (host, port.parse().ok()?),
+            None => (authority, 80),
+        },
+    };
+    if host.is_empty() || host.len() > 255 {
+        return None;
+    }
+    Some((host.to_string(), port, path.to_string()))
+}
+
+fn is_reset(err: &std::io::Error) -> bool {
+    matches!(
+        err.kind(),
+        ErrorKind::ConnectionReset | ErrorKind::ConnectionAborted | ErrorKind::UnexpectedEof
+    )
+}
+
+fn io_error_latency(err: &std::io::Error) -> Latency {
+    match err.kind() {
+        ErrorKind::TimedOut | ErrorKind::WouldBlock => Latency::Timeout,
+        ErrorKind::ConnectionRefused => Latency::Failed("refused".to_string()),
+        ErrorKind::HostUnreachable | ErrorKind::NetworkUnreachable => {
+            Latency::Failed("unreachable".to_string())
+        }
+        _ => Latency::Failed("error".to_string()),
+    }
+}
+
+/// Periodically probes a url through the running sslocal's SOCKS port.
+pub struct HealthMonitor {
+    stop: Arc<AtomicBool>,
+    rx: Receiver<Latency>,
+}
+
+impl HealthMonitor {
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    pub fn start(proxy: SocketAddr, url: String, interval: Duration) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let (tx, rx) = channel();
+        let stop_cloned = stop.clone();
+        std::thread::spawn(move || loop {
+            let start = Instant::now();
+            while start.elapsed() < interval {
+                if stop_cloned.load(Ordering::Relaxed) {
+                    return;
+                }
+                std::thread::sleep(Duration::from_millis(100));
+            }
+            if tx.send(http_probe(proxy, &url, Self::TIMEOUT)).is_err() {
+                break;
+            }
+        });
+        Self { stop, rx }
+    }
+
+    pub fn try_recv(&self) -> Vec<Latency> {
+        self.rx.try_iter().collect()
+    }
+}
+
+impl Drop for HealthMonitor {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Tests every server of a group on a small pool of worker threads, results
+/// arrive as (server index, latency) through `try_recv`.
+pub struct LatencyTest {
+    pub group_index: usize,
+    cancel_token: Arc<AtomicBool>,
+    rx: Receiver<(usize, Latency)>,
+    remaining: usize,
+}
+
+impl LatencyTest {
+    const WORKERS: usize = 16;
+    pub const TCP_TIMEOUT: Duration = Duration::from_secs(3);
+
+    const REAL_WORKERS: usize = 4;
+    pub const REAL_TIMEOUT: Duration = Duration::from_secs(5);
+
+    pub fn tcp(group_index: usize, servers: &[SSServer]) -> Self {
+        let queue: VecDeque<(usize, String)> =
+            servers.iter().map(SSServer::address).enumerate().collect();
+        Self::spawn(group_index, queue, Self::WORKERS, |address| {
+            tcp_ping(&address, Self::TCP_TIMEOUT)
+        })
+    }
+
+    /// Each server gets its own sslocal, so fewer run at once.
+    pub fn real(group_index: usize, servers: &[SSServer], sslocal: SSLocal, url: String) -> Self {
+        let queue: VecDeque<(usize, SSServer)> = servers.iter().cloned().enumerate().collect();
+        Self::spawn(group_index, queue, Self::REAL_WORKERS, move |server| {
+            real_ping(&sslocal, &server, &url, Self::REAL_TIMEOUT)
+        })
+    }
+
+    fn spawn<T, F>(group_index: usize, queue: VecDeque<(usize, T)>, workers: usize, test: F) -> Self
+    where
+        T: Send + 'static,
+        F: Fn(T) -> Latency + Send + Sync + 'static,
+    {
+        let remaining = queue.len();
+        let cancel_token = Arc::new(AtomicBool::new(false));
+        let queue = Arc::new(Mutex::new(queue));
+        let test = Arc::new(test);
+        let (tx, rx) = channel();
+        for _ in 0..workers.min(remaining) {
+            let cancel_token = cancel_token.clone();
+            let queue = queue.clone();
+            let test = test.clone();
+            let tx = tx.clone();
+            std::thread::spawn(move || loop {
+                if cancel_token.load(Ordering::Relaxed) {
+                    break;
+                }
+                let Some((i, item)) = queue.lock().unwrap().pop_front() else {
+                    break;
+                };
+                if tx.send((i, test(item))).is_err() {
+                    break;
+                }
+            });
+        }
+        Self {
+            group_index,
+            cancel_token,
+            rx,
+            remaining,
+        }
+    }
+
+    pub fn try_recv(&mut self) -> Vec<(usize, Latency)> {
+        let results: Vec<_> = self.rx.try_iter().collect();
+        self.remaining -= results.len();
+        results
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.remaining == 0
+    }
+
+    pub fn cancel(&self) {
+        self.cancel_token.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Drop for LatencyTest {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Copy)]
+    enum Peer {
+        /// Answers the HTTP request with a 204.
+        NoContent,
+        /// Closes the connection right after CONNECT.
+        Close,
+        /// Never answers the HTTP request.
+        Silent,
+    }
+
+    /// A one-shot SOCKS5 proxy that plays `peer` as the target behind it.
+    fn serve(peer: Peer) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut greeting = [0u8; 3];
+            stream.read_exact(&mut greeting).unwrap();
+            assert_eq!(greeting, [5, 1, 0]);
+            stream.write_all(&[5, 0]).unwrap();
+
+            let mut head = [0u8; 5];
+            stream.read_exact(&mut head).unwrap();
+            assert_eq!(head[..4], [5, 1, 0, 3]);
+            let mut target = vec![0u8; head[4] as usize + 2];
+            stream.read_exact(&mut target).unwrap();
+            stream
+                .write_all(&[5, 0, 0, 1, 127, 0, 0, 1, 0, 80])
+                .unwrap();
+
+            match peer {
+                Peer::NoContent => {
+                    let mut request = Vec::new();
+                    let mut byte = [0u8; 1];
+                    while !request.ends_with(b"\r\n\r\n") {
+                        stream.read_exact(&mut byte).unwrap();
+                        request.push(byte[0]);
+                    }
+                    assert!(request.starts_with(b"GET /generate_204 HTTP/1.1\r\n"));
+                    stream
+                        .write_all(b"HTTP/1.1 204 No Content\r\nContent-Length: 0\r\n\r\n")
+                        .unwrap();
+                }
+                Peer::Close => drop(stream),
+                Peer::Silent => {
+                    std::thread::sleep(Duration::from_secs(2));
+                    drop(stream);
+                }
+            }
+        });
+        addr
+    }
+
+    fn probe(peer: Peer) -> Latency {
+        http_probe(
+            serve(peer),
+            "http://example.com/generate_204",
+            Duration::from_millis(500),
+        )
+    }
+
+    #[test]
+    fn http_probe_ok_on_204() {
+        assert!(matches!(probe(Peer::NoContent), Latency::Ok(_)));
+    }
+
+    #[test]
+    fn http_probe_rejected_when_closed_after_connect() {
+        assert!(matches!(probe(Peer::Close), Latency::Rejected));
+    }
+
+    #[test]
+    fn http_probe_timeout_when_silent() {
+        assert!(matches!(probe(Peer::Silent), Latency::Timeout));
+    }
+}
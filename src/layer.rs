@@ -1,5 +1,9 @@
-use ratatui::crossterm::event::{poll, read, Event};
+use ratatui::crossterm::{
+    event::{poll, read, DisableBracketedPaste, EnableBracketedPaste, Event},
+    execute,
+};
 use std::{
+    io::stdout,
     sync::{OnceLock, RwLock},
     time::Duration,
 };
@@ -35,9 +39,16 @@ pub trait Layer {
 }
 
 pub fn terminal_init(terminal: ratatui::DefaultTerminal) {
+    // pasted text arrives as a single `Event::Paste` instead of key presses
+    let _ = execute!(stdout(), EnableBracketedPaste);
     TERMINAL.set(RwLock::new(terminal)).unwrap();
 }
 
+pub fn terminal_restore() {
+    let _ = execute!(stdout(), DisableBracketedPaste);
+    ratatui::restore();
+}
+
 pub fn terminal_init_default() {
     terminal_init(ratatui::init());
 }
@@ -1,4 +1,7 @@
+mod acl;
+mod latency;
 mod layer;
+mod logbuffer;
 mod sslocal;
 mod userdata;
 mod views;
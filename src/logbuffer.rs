@@ -0,0 +1,232 @@
+use std::{
+    collections::VecDeque,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    pub const ALL: [LogLevel; 5] = [
+        LogLevel::Error,
+        LogLevel::Warn,
+        LogLevel::Info,
+        LogLevel::Debug,
+        LogLevel::Trace,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LogLevel::Error => "ERROR",
+            LogLevel::Warn => "WARN",
+            LogLevel::Info => "INFO",
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Trace => "TRACE",
+        }
+    }
+
+    /// Finds the level among the first few words of a sslocal log line, e.g.
+    /// `2024-05-01T12:00:00.123+08:00 INFO  shadowsocks_rust: ...`.
+    pub fn parse(line: &str) -> Option<Self> {
+        line.split_whitespace().take(3).find_map(|word| {
+            let word = word.trim_matches(|c| c == '[' || c == ']');
+            LogLevel::ALL.into_iter().find(|level| level.name() == word)
+        })
+    }
+
+    pub fn next(self) -> Self {
+        let i = LogLevel::ALL.iter().position(|l| *l == self).unwrap();
+        LogLevel::ALL[(i + 1) % LogLevel::ALL.len()]
+    }
+}
+
+pub struct LogLine {
+    pub level: Option<LogLevel>,
+    pub text: String,
+}
+
+/// The last `CAPACITY` lines of sslocal output. Every line ever pushed has a
+/// sequence number, so a view can keep its position while old lines drop out.
+pub struct LogBuffer {
+    lines: VecDeque<LogLine>,
+    first_seq: u64,
+    file: Option<LogFile>,
+}
+
+impl Default for LogBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LogBuffer {
+    const CAPACITY: usize = 5000;
+
+    pub fn new() -> Self {
+        Self {
+            lines: VecDeque::new(),
+            first_seq: 0,
+            file: None,
+        }
+    }
+
+    pub fn push(&mut self, level: Option<LogLevel>, text: &str) {
+        if self.lines.len() == Self::CAPACITY {
+            self.lines.pop_front();
+            self.first_seq += 1;
+        }
+        let text = text.trim_end();
+        if let Some(file) = &mut self.file {
+            if let Err(err) = file.write_line(text) {
+                self.file = None;
+                self.push(
+                    Some(LogLevel::Error),
+                    &format!("log file disabled: {}", err),
+                );
+            }
+        }
+        self.lines.push_back(LogLine {
+            level,
+            text: text.to_string(),
+        });
+    }
+
+    /// Pushes a line of sslocal output. Lines without a level, like the rest
+    /// of a multi-line message, keep the level of the line before them.
+    pub fn push_output(&mut self, text: &str) {
+        let level = LogLevel::parse(text).or_else(|| self.lines.back().and_then(|l| l.level));
+        self.push(level, text);
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (u64, &LogLine)> {
+        let first_seq = self.first_seq;
+        self.lines
+            .iter()
+            .enumerate()
+            .map(move |(i, line)| (first_seq + i as u64, line))
+    }
+
+    pub fn get(&self, seq: u64) -> Option<&LogLine> {
+        seq.checked_sub(self.first_seq)
+            .and_then(|i| self.lines.get(i as usize))
+    }
+}
+
+impl LogBuffer {
+    /// Also writes every new line to `file`, or stops doing so with `None`.
+    pub fn set_file(&mut self, file: Option<LogFile>) {
+        self.file = file;
+    }
+
+    pub fn has_file(&self) -> bool {
+        self.file.is_some()
+    }
+
+    /// Writes the lines currently held to `path`.
+    pub fn export(&self, path: &Path) -> io::Result<()> {
+        let mut content = String::new();
+        for line in &self.lines {
+            content.push_str(&line.text);
+            content.push('\n');
+        }
+        fs::write(path, content)
+    }
+}
+
+/// Appends to `dir/sslocal.log`. A full file moves to `sslocal.1.log`, the
+/// previous one to `sslocal.2.log` and so on, keeping `max_files` in total.
+pub struct LogFile {
+    dir: PathBuf,
+    max_bytes: u64,
+    max_files: usize,
+    file: File,
+    size: u64,
+}
+
+impl LogFile {
+    pub fn open(dir: PathBuf, max_bytes: u64, max_files: usize) -> io::Result<Self> {
+        fs::create_dir_all(&dir)?;
+        let path = dir.join("sslocal.log");
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            dir,
+            max_bytes,
+            max_files: max_files.max(1),
+            file,
+            size,
+        })
+    }
+
+    fn path(&self, index: usize) -> PathBuf {
+        if index == 0 {
+            self.dir.join("sslocal.log")
+        } else {
+            self.dir.join(format!("sslocal.{}.log", index))
+        }
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        for i in (1..self.max_files).rev() {
+            let from = self.path(i - 1);
+            if from.exists() {
+                fs::rename(from, self.path(i))?;
+            }
+        }
+        self.file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(self.path(0))?;
+        self.size = 0;
+        Ok(())
+    }
+
+    fn write_line(&mut self, line: &str) -> io::Result<()> {
+        let len = line.len() as u64 + 1;
+        if self.size > 0 && self.size + len > self.max_bytes {
+            self.rotate()?;
+        }
+        writeln!(self.file, "{}", line)?;
+        self.size += len;
+        Ok(())
+    }
+}
+
+/// Current UTC time as `YYYYMMDD-HHMMSS`, for file names.
+pub fn utc_timestamp() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let (days, secs) = ((secs / 86400) as i64, secs % 86400);
+    // days since 1970-01-01 to a civil date, see
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}{:02}{:02}-{:02}{:02}{:02}",
+        year,
+        month,
+        day,
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
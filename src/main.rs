@@ -1,8 +1,8 @@
-use sstui::{terminal_init_default, Layer, MainLayer};
+use sstui::{terminal_init_default, terminal_restore, Layer, MainLayer};
 
 fn main() {
     terminal_init_default();
     let result = MainLayer::new().show();
-    ratatui::restore();
+    terminal_restore();
     result.unwrap();
 }
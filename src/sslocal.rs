@@ -1,67 +1,534 @@
 use std::{
-    env::current_exe,
-    io::Cursor,
-    path::PathBuf,
-    process::{Child, Command, Stdio},
-    sync::mpsc::Sender,
+    env::{self, consts::EXE_SUFFIX, current_exe},
+    fs::{self, OpenOptions},
+    io::{Cursor, ErrorKind, Write},
+    net::{TcpListener, UdpSocket},
+    ops::{Deref, DerefMut},
+    path::{Path, PathBuf},
+    process::{self, Child, Command, Stdio},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::Sender,
+    },
 };
 
-use serde::Deserialize;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
 use xz2::read::XzDecoder;
 use zip::ZipArchive;
 
-use crate::userdata::SSServer;
+use crate::userdata::{parse_host_port, DnsConfig, Listener, ListenerProtocol, SSServer};
 
+#[derive(Clone)]
 pub struct SSLocal {
     exec_path: PathBuf,
     pub version: String,
+    /// `version` parsed, `None` if sslocal printed something unexpected.
+    pub semver: Option<Version>,
 }
 
 impl SSLocal {
     pub fn new(exec_path: PathBuf) -> std::io::Result<Self> {
         let output = Command::new(&exec_path).arg("--version").output()?;
         let version = String::from_utf8_lossy(&output.stdout).to_string();
-        Ok(Self { exec_path, version })
+        let semver = parse_version(&version);
+        Ok(Self {
+            exec_path,
+            version,
+            semver,
+        })
+    }
+
+    /// Looks for a SIP003 plugin next to sslocal first, then on `PATH`.
+    fn find_plugin(&self, plugin: &str) -> std::io::Result<PathBuf> {
+        let path = Path::new(plugin);
+        if path.components().count() > 1 && path.is_file() {
+            return Ok(path.to_path_buf());
+        }
+        let ss_dir = self.exec_path.parent().unwrap_or(Path::new("."));
+        let mut dirs = vec![ss_dir.to_path_buf()];
+        // plugins dropped into `ss/` serve every installed version
+        if let Ok(root) = SSLocalManager::ss_dir() {
+            dirs.push(root);
+        }
+        if let Some(paths) = env::var_os("PATH") {
+            dirs.extend(env::split_paths(&paths));
+        }
+        for dir in dirs {
+            for name in [plugin.to_string(), format!("{}{}", plugin, EXE_SUFFIX)] {
+                let candidate = dir.join(name);
+                if candidate.is_file() {
+                    return Ok(candidate);
+                }
+            }
+        }
+        Err(std::io::Error::new(
+            ErrorKind::NotFound,
+            format!(
+                "plugin '{}' not found in '{}' or PATH",
+                plugin,
+                ss_dir.display()
+            ),
+        ))
+    }
+
+    /// The shadowsocks-rust config for `server`. Secrets only ever go through
+    /// this file, never through the command line where `ps` can see them.
+    fn config(
+        &self,
+        server: &SSServer,
+        locals: Vec<serde_json::Value>,
+    ) -> std::io::Result<serde_json::Value> {
+        let mut ss_server = json!({
+            "server": server.server,
+            "server_port": server.server_port,
+            "method": server.method,
+            "password": server.password,
+        });
+        if let Some(plugin) = &server.plugin {
+            ss_server["plugin"] = json!(self.find_plugin(plugin)?);
+            if let Some(plugin_opts) = &server.plugin_opts {
+                ss_server["plugin_opts"] = json!(plugin_opts);
+            }
+        }
+        Ok(json!({
+            "servers": [ss_server],
+            "locals": locals,
+        }))
+    }
+
+    fn spawn(
+        &self,
+        config: &serde_json::Value,
+        command: impl FnOnce(&mut Command),
+    ) -> std::io::Result<SSLocalChild> {
+        let config = ConfigFile::create(config)?;
+        let mut cmd = Command::new(&self.exec_path);
+        cmd.arg("-c").arg(&config.path);
+        command(&mut cmd);
+        Ok(SSLocalChild {
+            child: cmd.spawn()?,
+            _config: config,
+        })
     }
 
     pub fn run(
         &self,
         server: &SSServer,
-        local_port: u16,
-        lan_support: bool,
-    ) -> std::io::Result<Child> {
-        let local_addr = if lan_support {
-            format!("0.0.0.0:{}", local_port)
+        listeners: &[Listener],
+        dns: &DnsConfig,
+        acl: Option<&Path>,
+    ) -> std::io::Result<SSLocalChild> {
+        let mut ports: Vec<_> = listeners
+            .iter()
+            .filter(|l| l.protocol != ListenerProtocol::Tun)
+            .map(|l| {
+                let udp = l.protocol == ListenerProtocol::Socks || l.needs_net_admin();
+                (l.protocol.name(), l.address(), l.port, udp)
+            })
+            .collect();
+        if dns.enabled {
+            ports.push(("DNS", dns.address(), dns.port, true));
+        }
+        check_ports(&ports)?;
+        check_capabilities(&self.exec_path, listeners)?;
+        let mut locals: Vec<_> = listeners.iter().map(local_config).collect();
+        if dns.enabled {
+            locals.push(dns_config(dns)?);
+        }
+        let config = self.config(server, locals)?;
+        self.spawn(&config, |command| {
+            command
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .arg("-v");
+            if let Some(acl) = acl {
+                command.arg("--acl").arg(acl);
+            }
+        })
+    }
+
+    /// A silent, TCP only instance on `127.0.0.1:local_port` for testing a server.
+    pub fn run_probe(&self, server: &SSServer, local_port: u16) -> std::io::Result<SSLocalChild> {
+        let local = json!({
+            "local_address": "127.0.0.1",
+            "local_port": local_port,
+        });
+        let config = self.config(server, vec![local])?;
+        self.spawn(&config, |command| {
+            command.stdout(Stdio::null()).stderr(Stdio::null());
+        })
+    }
+}
+
+/// The `locals` entry of a listener.
+fn local_config(listener: &Listener) -> serde_json::Value {
+    if listener.protocol == ListenerProtocol::Tun {
+        return json!({
+            "protocol": listener.protocol,
+            "tun_interface_name": TUN_NAME,
+            "tun_interface_address": TUN_ADDRESS,
+        });
+    }
+    let mut local = json!({
+        "protocol": listener.protocol,
+        "local_address": listener.address(),
+        "local_port": listener.port,
+    });
+    match listener.protocol {
+        ListenerProtocol::Socks => local["mode"] = json!("tcp_and_udp"),
+        // UDP can only be redirected with tproxy
+        ListenerProtocol::Redir if listener.tproxy => {
+            local["mode"] = json!("tcp_and_udp");
+            local["tcp_redir"] = json!("tproxy");
+            local["udp_redir"] = json!("tproxy");
+        }
+        ListenerProtocol::Redir => local["tcp_redir"] = json!("redirect"),
+        _ => {}
+    }
+    local
+}
+
+fn dns_config(dns: &DnsConfig) -> std::io::Result<serde_json::Value> {
+    let upstream = |host_port: &str| {
+        parse_host_port(host_port)
+            .map_err(|err| std::io::Error::new(ErrorKind::InvalidInput, format!("dns: {}", err)))
+    };
+    let (local_host, local_port) = upstream(&dns.local_upstream)?;
+    let (remote_host, remote_port) = upstream(&dns.remote_upstream)?;
+    Ok(json!({
+        "protocol": "dns",
+        "local_address": dns.address(),
+        "local_port": dns.port,
+        "mode": "tcp_and_udp",
+        "local_dns_address": local_host,
+        "local_dns_port": local_port,
+        "remote_dns_address": remote_host,
+        "remote_dns_port": remote_port,
+    }))
+}
+
+pub const TUN_NAME: &str = "sstui-tun";
+pub const TUN_ADDRESS: &str = "10.255.0.1/24";
+
+/// Fails if two listeners share a port or a port is taken by another program.
+/// Each entry is (name, address, port, uses udp).
+fn check_ports(ports: &[(&str, &str, u16, bool)]) -> std::io::Result<()> {
+    for (i, (name, address, port, udp)) in ports.iter().enumerate() {
+        if ports[..i].iter().any(|(_, _, p, _)| p == port) {
+            return Err(std::io::Error::new(
+                ErrorKind::AddrInUse,
+                format!("port {} is used by more than one listener", port),
+            ));
+        }
+        let bound = TcpListener::bind((*address, *port)).map(|_| ());
+        let bound = if *udp {
+            bound.and_then(|_| UdpSocket::bind((*address, *port)).map(|_| ()))
         } else {
-            format!("127.0.0.1:{}", local_port)
+            bound
         };
-        Command::new(&self.exec_path)
-            .stdout(Stdio::piped())
-            .arg("-b")
-            .arg(local_addr)
-            .arg("-s")
-            .arg(format!("{}:{}", &server.server, &server.server_port))
-            .arg("-m")
-            .arg(&server.method)
-            .arg("-k")
-            .arg(&server.password)
-            .arg("-U")
-            .arg("-v")
-            .spawn()
-    }
-}
-
-#[derive(Debug, Deserialize)]
-pub struct LatestRelease {
+        if let Err(err) = bound {
+            return Err(std::io::Error::new(
+                err.kind(),
+                format!("{} listener port {} is not available: {}", name, port, err),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Tun and tproxy redir need CAP_NET_ADMIN. sslocal has it when we run as
+/// root, when it is in our ambient set, or from file capabilities on the
+/// sslocal binary itself.
+#[cfg(target_os = "linux")]
+fn check_capabilities(exec_path: &Path, listeners: &[Listener]) -> std::io::Result<()> {
+    const CAP_NET_ADMIN: u32 = 12;
+    let Some(listener) = listeners.iter().find(|l| l.needs_net_admin()) else {
+        return Ok(());
+    };
+
+    let status = fs::read_to_string("/proc/self/status").unwrap_or_default();
+    let field = |name: &str| {
+        status
+            .lines()
+            .find_map(|line| line.strip_prefix(name))
+            .map(str::trim)
+    };
+    let caps = |name: &str| {
+        field(name)
+            .and_then(|caps| u64::from_str_radix(caps, 16).ok())
+            .unwrap_or(0)
+    };
+    // Uid: real, effective, saved, filesystem
+    let is_root = field("Uid:").and_then(|uids| uids.split_whitespace().nth(1)) == Some("0");
+    let inherited = if is_root {
+        caps("CapEff:")
+    } else {
+        caps("CapAmb:")
+    };
+    if inherited & (1 << CAP_NET_ADMIN) != 0 {
+        return Ok(());
+    }
+
+    // vfs_cap_data: magic_etc with the effective flag in bit 0, then the
+    // low 32 bits of the permitted set
+    if let Ok(Some(data)) = xattr::get(exec_path, "security.capability") {
+        if data.len() >= 8 {
+            let magic_etc = u32::from_le_bytes([data[0], data[1], data[2], data[3]]);
+            let permitted = u32::from_le_bytes([data[4], data[5], data[6], data[7]]);
+            if magic_etc & 1 != 0 && permitted & (1 << CAP_NET_ADMIN) != 0 {
+                return Ok(());
+            }
+        }
+    }
+
+    Err(std::io::Error::new(
+        ErrorKind::PermissionDenied,
+        format!(
+            "the {} listener needs CAP_NET_ADMIN, run sstui as root or grant it to sslocal with 'sudo setcap cap_net_admin+ep {}'",
+            listener.protocol.name(),
+            exec_path.display()
+        ),
+    ))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn check_capabilities(_exec_path: &Path, _listeners: &[Listener]) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// A private temp file holding a generated config, removed on drop.
+struct ConfigFile {
+    path: PathBuf,
+}
+
+impl ConfigFile {
+    fn create(config: &serde_json::Value) -> std::io::Result<Self> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = env::temp_dir().join(format!(
+            "sstui-{}-{}.json",
+            process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(&path)?;
+        let config_file = Self { path };
+        file.write_all(&serde_json::to_vec_pretty(config)?)?;
+        Ok(config_file)
+    }
+}
+
+impl Drop for ConfigFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// A running sslocal together with its config file, which lives as long as
+/// this does.
+pub struct SSLocalChild {
+    child: Child,
+    _config: ConfigFile,
+}
+
+impl Deref for SSLocalChild {
+    type Target = Child;
+
+    fn deref(&self) -> &Child {
+        &self.child
+    }
+}
+
+impl DerefMut for SSLocalChild {
+    fn deref_mut(&mut self) -> &mut Child {
+        &mut self.child
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct Release {
     pub tag_name: String,
+    #[serde(default)]
+    pub prerelease: bool,
+    /// RFC 3339, e.g. `2024-10-15T08:30:00Z`.
+    #[serde(default)]
+    pub published_at: Option<String>,
+    /// The release notes in markdown.
+    #[serde(default)]
+    pub body: Option<String>,
     pub assets: Vec<Asset>,
 }
 
+impl Release {
+    /// The day part of `published_at`.
+    pub fn published_date(&self) -> &str {
+        self.published_at
+            .as_deref()
+            .and_then(|at| at.split_once('T'))
+            .map_or("-", |(date, _)| date)
+    }
+
+    /// Pairs every asset with its `.sha256` sidecar, dropping the sidecars
+    /// from the list.
+    fn attach_sidecars(&mut self) {
+        let (sidecars, mut assets): (Vec<Asset>, Vec<Asset>) = std::mem::take(&mut self.assets)
+            .into_iter()
+            .partition(|asset| asset.name.ends_with(".sha256"));
+        for asset in &mut assets {
+            let sidecar_name = format!("{}.sha256", asset.name);
+            asset.sha256_url = sidecars
+                .iter()
+                .find(|sidecar| sidecar.name == sidecar_name)
+                .map(|sidecar| sidecar.browser_download_url.clone());
+        }
+        self.assets = assets;
+    }
+
+    pub fn version(&self) -> Option<Version> {
+        parse_version(&self.tag_name)
+    }
+
+    /// Whether this release upgrades `installed`, when either version can't
+    /// be parsed it is offered anyway.
+    pub fn is_newer_than(&self, installed: Option<&SSLocal>) -> bool {
+        match (self.version(), installed.and_then(|s| s.semver.as_ref())) {
+            (Some(latest), Some(installed)) => latest > *installed,
+            _ => true,
+        }
+    }
+
+    /// The archive built for this machine, an exact libc match first.
+    pub fn matching_asset(&self) -> Option<&Asset> {
+        let target = Target::current();
+        let compatible = || {
+            self.assets
+                .iter()
+                .filter(move |asset| asset.target().is_some_and(|t| target.is_compatible(t)))
+        };
+        compatible()
+            .find(|asset| asset.target().is_some_and(|t| target.is_exact(t)))
+            .or_else(|| compatible().next())
+    }
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct Asset {
     pub name: String,
     pub size: usize,
     pub browser_download_url: String,
+    /// The published `<name>.sha256` sidecar.
+    #[serde(skip)]
+    pub sha256_url: Option<String>,
+}
+
+impl Asset {
+    pub fn is_archive(&self) -> bool {
+        self.name.ends_with(".zip") || self.name.ends_with(".tar.xz")
+    }
+
+    /// The target triple of a `shadowsocks-<tag>.<triple>.<ext>` archive.
+    pub fn target(&self) -> Option<&str> {
+        let stem = self
+            .name
+            .strip_suffix(".tar.xz")
+            .or_else(|| self.name.strip_suffix(".zip"))?;
+        if !stem.starts_with("shadowsocks-") {
+            return None;
+        }
+        stem.rsplit_once('.').map(|(_, triple)| triple)
+    }
+}
+
+/// The first word that is a version, like `1.21.2` in `shadowsocks 1.21.2`
+/// or `v1.21.2`.
+pub fn parse_version(text: &str) -> Option<Version> {
+    text.split_whitespace()
+        .find_map(|word| Version::parse(word.trim_start_matches('v')).ok())
+}
+
+/// The platform sstui was built for, in the terms of a Rust target triple.
+#[derive(Clone, Copy)]
+pub struct Target {
+    arch: &'static str,
+    os: &'static str,
+    env: &'static str,
+}
+
+impl Target {
+    pub fn current() -> Self {
+        let os = match env::consts::OS {
+            "macos" => "darwin",
+            os => os,
+        };
+        let env = if cfg!(target_env = "musl") {
+            "musl"
+        } else if cfg!(target_env = "msvc") {
+            "msvc"
+        } else if cfg!(target_env = "gnu") {
+            "gnu"
+        } else {
+            ""
+        };
+        Self {
+            arch: env::consts::ARCH,
+            os,
+            env,
+        }
+    }
+
+    fn matches_arch(&self, arch: &str) -> bool {
+        match self.arch {
+            "x86" => matches!(arch, "i686" | "i586" | "i386"),
+            "arm" => arch.starts_with("arm"),
+            "riscv64" => arch.starts_with("riscv64"),
+            current => arch == current,
+        }
+    }
+
+    /// The libc part of a triple, e.g. `gnu` for `gnueabihf`.
+    fn triple_env(triple: &str) -> &str {
+        let last = triple.rsplit('-').next().unwrap_or_default();
+        ["musl", "gnu", "msvc"]
+            .into_iter()
+            .find(|env| last.starts_with(env))
+            .unwrap_or_default()
+    }
+
+    /// Whether a binary built for `triple` runs here, musl builds are static
+    /// and run on any Linux.
+    pub fn is_compatible(&self, triple: &str) -> bool {
+        let mut parts = triple.split('-');
+        let arch_ok = parts.next().is_some_and(|arch| self.matches_arch(arch));
+        let os_ok = parts.any(|part| part == self.os);
+        let env = Self::triple_env(triple);
+        arch_ok && os_ok && (env == self.env || env == "musl" || self.env.is_empty())
+    }
+
+    pub fn is_exact(&self, triple: &str) -> bool {
+        self.is_compatible(triple) && Self::triple_env(triple) == self.env
+    }
+
+    pub fn name(&self) -> String {
+        [self.arch, self.os, self.env]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("-")
+    }
+}
+
+/// Which installed version is used, stored as `ss/versions.json`. A version
+/// is the name of its directory in `ss/`, or [`SSLocalManager::LEGACY`].
+#[derive(Serialize, Deserialize, Default)]
+pub struct InstalledVersions {
+    pub active: Option<String>,
+    /// What `active` was before, the rollback target.
+    pub previous: Option<String>,
 }
 
 pub struct SSLocalManager;
@@ -69,38 +536,230 @@ pub struct SSLocalManager;
 impl SSLocalManager {
     const CHECK_URL: &'static str =
         "https://api.github.com/repos/shadowsocks/shadowsocks-rust/releases/latest";
+    const RELEASES_URL: &'static str =
+        "https://api.github.com/repos/shadowsocks/shadowsocks-rust/releases?per_page=30";
 
-    pub fn find_sslocal() -> std::io::Result<Option<SSLocal>> {
+    /// sslocal installed straight into `ss/` by older versions of sstui.
+    pub const LEGACY: &'static str = "legacy";
+
+    pub fn ss_dir() -> std::io::Result<PathBuf> {
         let mut dir = current_exe()?;
         dir.set_file_name("ss");
-        if dir.exists() && dir.is_dir() {
+        Ok(dir)
+    }
+
+    fn version_dir(version: &str) -> std::io::Result<PathBuf> {
+        let dir = Self::ss_dir()?;
+        if version == Self::LEGACY {
+            return Ok(dir);
+        }
+        // tags become directory names
+        if version.is_empty()
+            || version.starts_with('.')
+            || !version
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | '+'))
+        {
+            return Err(std::io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("'{}' is not a valid version", version),
+            ));
+        }
+        Ok(dir.join(version))
+    }
+
+    fn find_executable(dir: &Path) -> std::io::Result<Option<PathBuf>> {
+        if dir.is_dir() {
             for entry in dir.read_dir()? {
                 let path = entry?;
                 if path.file_type()?.is_file()
                     && path.file_name().to_string_lossy().contains("sslocal")
                 {
-                    let sslocal = SSLocal::new(path.path())?;
-                    return Ok(Some(sslocal));
+                    return Ok(Some(path.path()));
                 }
             }
         }
         Ok(None)
     }
 
-    fn _get_latest(agent: ureq::Agent) -> anyhow::Result<LatestRelease> {
-        let mut latest_release: LatestRelease = agent.get(Self::CHECK_URL).call()?.into_json()?;
-        latest_release
-            .assets
-            .retain(|asset| !asset.name.ends_with(".sha256"));
+    pub fn load_versions() -> std::io::Result<InstalledVersions> {
+        let path = Self::ss_dir()?.join("versions.json");
+        if !path.exists() {
+            return Ok(InstalledVersions::default());
+        }
+        Ok(serde_json::from_slice(&fs::read(path)?)?)
+    }
+
+    fn save_versions(versions: &InstalledVersions) -> std::io::Result<()> {
+        let dir = Self::ss_dir()?;
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join("versions.json"), serde_json::to_vec(versions)?)
+    }
+
+    /// Installed versions, newest first, the legacy install last.
+    pub fn installed() -> std::io::Result<Vec<String>> {
+        let dir = Self::ss_dir()?;
+        let mut versions = Vec::new();
+        if dir.is_dir() {
+            for entry in dir.read_dir()? {
+                let entry = entry?;
+                // skips unfinished installs in `ss/.<version>.tmp/`
+                if entry.file_type()?.is_dir()
+                    && !entry.file_name().to_string_lossy().starts_with('.')
+                    && Self::find_executable(&entry.path())?.is_some()
+                {
+                    versions.push(entry.file_name().to_string_lossy().to_string());
+                }
+            }
+        }
+        versions.sort_by(|a, b| {
+            parse_version(b)
+                .cmp(&parse_version(a))
+                .then_with(|| b.cmp(a))
+        });
+        if Self::find_executable(&dir)?.is_some() {
+            versions.push(Self::LEGACY.to_string());
+        }
+        Ok(versions)
+    }
+
+    /// The version in use and its executable: the active one, or the newest
+    /// installed one if the active version is gone. `skip` is left out.
+    fn resolve(skip: Option<&str>) -> std::io::Result<Option<(String, PathBuf)>> {
+        let mut candidates = Self::installed()?;
+        if let Some(active) = Self::load_versions()?.active {
+            candidates.insert(0, active);
+        }
+        for version in candidates {
+            if Some(version.as_str()) == skip {
+                continue;
+            }
+            if let Some(path) = Self::find_executable(&Self::version_dir(&version)?)? {
+                return Ok(Some((version, path)));
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn find_sslocal() -> std::io::Result<Option<SSLocal>> {
+        match Self::resolve(None)? {
+            Some((_, path)) => Ok(Some(SSLocal::new(path)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// The name of the version `find_sslocal` picks.
+    pub fn active_version() -> std::io::Result<Option<String>> {
+        Ok(Self::resolve(None)?.map(|(version, _)| version))
+    }
+
+    /// Runs `sslocal --version` of an installed version, it has to exit
+    /// cleanly and print a version.
+    fn smoke_test(dir: &Path) -> anyhow::Result<SSLocal> {
+        let Some(path) = Self::find_executable(dir)? else {
+            anyhow::bail!("no sslocal in '{}'", dir.display());
+        };
+        let output = Command::new(&path).arg("--version").output()?;
+        if !output.status.success() {
+            anyhow::bail!("'sslocal --version' exited with {}", output.status);
+        }
+        let sslocal = SSLocal::new(path)?;
+        if sslocal.semver.is_none() {
+            anyhow::bail!("'sslocal --version' printed '{}'", sslocal.version.trim());
+        }
+        Ok(sslocal)
+    }
+
+    /// Makes `version` the active one if it passes the smoke test, otherwise
+    /// the active version stays as it is.
+    pub fn activate(version: &str) -> anyhow::Result<SSLocal> {
+        let sslocal = Self::smoke_test(&Self::version_dir(version)?)?;
+        let mut versions = Self::load_versions()?;
+        if versions.active.as_deref() != Some(version) {
+            versions.previous = Self::resolve(Some(version))?.map(|(current, _)| current);
+        }
+        versions.active = Some(version.to_string());
+        Self::save_versions(&versions)?;
+        Ok(sslocal)
+    }
+
+    /// Moves a freshly extracted version from its staging directory into
+    /// place and activates it. A broken one is thrown away, leaving whatever
+    /// was installed, and the version used before, untouched.
+    pub fn activate_new(version: &str) -> anyhow::Result<SSLocal> {
+        let staging = Self::staging_dir(version)?;
+        if let Err(err) = Self::smoke_test(&staging) {
+            let _ = fs::remove_dir_all(&staging);
+            anyhow::bail!("sslocal {} failed its smoke test, {}", version, err);
+        }
+        let dir = Self::version_dir(version)?;
+        let replaced = if dir.exists() {
+            fs::remove_dir_all(&dir)
+        } else {
+            Ok(())
+        };
+        if let Err(err) = replaced.and_then(|_| fs::rename(&staging, &dir)) {
+            let _ = fs::remove_dir_all(&staging);
+            anyhow::bail!("installing sslocal {} failed, {}", version, err);
+        }
+        Self::activate(version)
+    }
+
+    /// Goes back to the previously active version.
+    pub fn rollback() -> anyhow::Result<SSLocal> {
+        let Some(previous) = Self::load_versions()?.previous else {
+            anyhow::bail!("no previous version to roll back to");
+        };
+        Self::activate(&previous)
+    }
+
+    /// Removes an installed version, the active one can't be removed.
+    pub fn uninstall(version: &str) -> anyhow::Result<()> {
+        if Self::active_version()?.as_deref() == Some(version) {
+            anyhow::bail!("'{}' is active", version);
+        }
+        let mut versions = Self::load_versions()?;
+        if version == Self::LEGACY {
+            if let Some(path) = Self::find_executable(&Self::ss_dir()?)? {
+                fs::remove_file(path)?;
+            }
+        } else {
+            fs::remove_dir_all(Self::version_dir(version)?)?;
+        }
+        if versions.previous.as_deref() == Some(version) {
+            versions.previous = None;
+            Self::save_versions(&versions)?;
+        }
+        Ok(())
+    }
+
+    fn _get_latest(agent: ureq::Agent) -> anyhow::Result<Release> {
+        let mut latest_release: Release = agent.get(Self::CHECK_URL).call()?.into_json()?;
+        latest_release.attach_sidecars();
         Ok(latest_release)
     }
 
-    pub fn get_latest() -> anyhow::Result<LatestRelease> {
+    fn _get_releases(agent: ureq::Agent) -> anyhow::Result<Vec<Release>> {
+        let mut releases: Vec<Release> = agent.get(Self::RELEASES_URL).call()?.into_json()?;
+        for release in &mut releases {
+            release.attach_sidecars();
+        }
+        Ok(releases)
+    }
+
+    /// The most recent releases, newest first, pre-releases included.
+    pub fn get_releases() -> anyhow::Result<Vec<Release>> {
+        let agent = ureq::AgentBuilder::new().build();
+        Self::_get_releases(agent)
+    }
+
+    pub fn get_latest() -> anyhow::Result<Release> {
         let agent = ureq::AgentBuilder::new().build();
         Self::_get_latest(agent)
     }
 
-    pub fn get_latest_proxy<P: AsRef<str>>(proxy: P) -> anyhow::Result<LatestRelease> {
+    #[allow(dead_code)]
+    pub fn get_latest_proxy<P: AsRef<str>>(proxy: P) -> anyhow::Result<Release> {
         let proxy = ureq::Proxy::new(proxy)?;
         let agent = ureq::AgentBuilder::new().proxy(proxy).build();
         Self::_get_latest(agent)
@@ -127,6 +786,7 @@ impl SSLocalManager {
         Self::_download(agent, url, tx)
     }
 
+    #[allow(dead_code)]
     pub fn download_proxy<P: AsRef<str>>(
         url: &str,
         tx: Sender<Vec<u8>>,
@@ -137,20 +797,74 @@ impl SSLocalManager {
         Self::_download(agent, url, tx)
     }
 
-    pub fn extract_zip(bytes: &[u8]) -> zip::result::ZipResult<()> {
+    /// Checks `bytes` against the asset's published SHA-256 and returns the
+    /// hex digest.
+    pub fn verify_sha256(asset: &Asset, bytes: &[u8]) -> anyhow::Result<String> {
+        let Some(url) = &asset.sha256_url else {
+            anyhow::bail!("no .sha256 published for '{}'", asset.name);
+        };
+        let agent = ureq::AgentBuilder::new().build();
+        let sidecar = agent.get(url).call()?.into_string()?;
+        // `<hex digest>  <file name>`
+        let Some(expected) = sidecar.split_whitespace().next() else {
+            anyhow::bail!("empty .sha256 for '{}'", asset.name);
+        };
+        let actual: String = Sha256::digest(bytes)
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        if !expected.eq_ignore_ascii_case(&actual) {
+            anyhow::bail!(
+                "SHA-256 mismatch for '{}', expected {} but got {}",
+                asset.name,
+                expected,
+                actual
+            );
+        }
+        Ok(actual)
+    }
+
+    /// Records the digest of the installed archive next to sslocal.
+    pub fn record_sha256(version: &str, asset: &Asset, digest: &str) -> std::io::Result<()> {
+        fs::write(
+            Self::staging_dir(version)?.join("release.sha256"),
+            format!("{}  {}\n", digest, asset.name),
+        )
+    }
+
+    /// `ss/.<version>.tmp/`, where a download is extracted until it passed
+    /// the smoke test.
+    fn staging_dir(version: &str) -> std::io::Result<PathBuf> {
+        if version == Self::LEGACY {
+            return Err(std::io::Error::new(
+                ErrorKind::InvalidInput,
+                "can't install over the legacy layout",
+            ));
+        }
+        Self::version_dir(version)?;
+        Ok(Self::ss_dir()?.join(format!(".{}.tmp", version)))
+    }
+
+    /// An empty staging directory to extract into.
+    fn clean_staging_dir(version: &str) -> std::io::Result<PathBuf> {
+        let dir = Self::staging_dir(version)?;
+        if dir.exists() {
+            fs::remove_dir_all(&dir)?;
+        }
+        fs::create_dir_all(&dir)?;
+        Ok(dir)
+    }
+
+    pub fn extract_zip(version: &str, bytes: &[u8]) -> zip::result::ZipResult<()> {
         let mut zip = ZipArchive::new(Cursor::new(bytes))?;
-        let mut dir = current_exe()?;
-        dir.set_file_name("ss");
-        zip.extract(dir)?;
+        zip.extract(Self::clean_staging_dir(version)?)?;
         Ok(())
     }
 
-    pub fn extract_tar_xz(bytes: &[u8]) -> zip::result::ZipResult<()> {
+    pub fn extract_tar_xz(version: &str, bytes: &[u8]) -> zip::result::ZipResult<()> {
         let xz = XzDecoder::new(Cursor::new(bytes));
         let mut tar = tar::Archive::new(xz);
-        let mut dir = current_exe()?;
-        dir.set_file_name("ss");
-        tar.unpack(dir)?;
+        tar.unpack(Self::clean_staging_dir(version)?)?;
         Ok(())
     }
 }
@@ -1,6 +1,10 @@
 use base64::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::{env::current_exe, fs, io};
+use std::{
+    cmp::Ordering, collections::BTreeMap, env::current_exe, fs, io, path::PathBuf, time::Duration,
+};
+
+use crate::latency::Latency;
 
 #[derive(Serialize, Deserialize)]
 pub struct UserData {
@@ -8,6 +12,183 @@ pub struct UserData {
     pub lan_support: bool,
     pub selected_server: Option<(usize, usize)>,
     pub server_groups: Vec<ServerGroup>,
+    /// Fetched through a temporary sslocal by the real delay test.
+    #[serde(default = "default_probe_url")]
+    pub probe_url: String,
+    #[serde(default)]
+    pub health_check: HealthCheck,
+    /// Restarts of a crashed sslocal before giving up.
+    #[serde(default = "default_max_restarts")]
+    pub max_restarts: u32,
+    #[serde(default)]
+    pub log_files: LogFiles,
+    /// Listeners besides the main SOCKS5 one on `local_port`.
+    #[serde(default)]
+    pub listeners: Vec<Listener>,
+    #[serde(default)]
+    pub dns: DnsConfig,
+    /// ACL rule set for groups without their own.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub acl: Option<String>,
+}
+
+fn default_max_restarts() -> u32 {
+    5
+}
+
+/// Probing the running sslocal and failing over to another server.
+#[derive(Serialize, Deserialize)]
+pub struct HealthCheck {
+    pub enabled: bool,
+    pub interval_secs: u64,
+    /// Consecutive failed probes before switching server.
+    pub max_failures: u32,
+}
+
+impl Default for HealthCheck {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: 30,
+            max_failures: 3,
+        }
+    }
+}
+
+/// Serialized with the names sslocal uses for a local's `protocol`.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ListenerProtocol {
+    Socks,
+    Http,
+    /// Transparent proxy for traffic sent here by iptables/nftables.
+    Redir,
+    /// Routes everything sent to the tun interface.
+    Tun,
+}
+
+impl ListenerProtocol {
+    pub const ALL: [ListenerProtocol; 4] = [
+        ListenerProtocol::Socks,
+        ListenerProtocol::Http,
+        ListenerProtocol::Redir,
+        ListenerProtocol::Tun,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ListenerProtocol::Socks => "SOCKS5",
+            ListenerProtocol::Http => "HTTP",
+            ListenerProtocol::Redir => "Redir",
+            ListenerProtocol::Tun => "TUN",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|p| *p == self).unwrap();
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+
+    pub fn previous(self) -> Self {
+        let i = Self::ALL.iter().position(|p| *p == self).unwrap();
+        Self::ALL[(i + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Listener {
+    pub protocol: ListenerProtocol,
+    pub port: u16,
+    /// Listen on all interfaces instead of loopback only.
+    pub lan: bool,
+    /// Redir only: TPROXY for TCP and UDP instead of REDIRECT for TCP.
+    #[serde(default)]
+    pub tproxy: bool,
+}
+
+impl Listener {
+    pub fn address(&self) -> &'static str {
+        if self.lan {
+            "0.0.0.0"
+        } else {
+            "127.0.0.1"
+        }
+    }
+
+    pub fn needs_net_admin(&self) -> bool {
+        match self.protocol {
+            ListenerProtocol::Tun => true,
+            ListenerProtocol::Redir => self.tproxy,
+            _ => false,
+        }
+    }
+}
+
+/// sslocal's local DNS forwarder. Names bypassed by the ACL are resolved by
+/// `local_upstream` directly, the rest by `remote_upstream` through the server.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DnsConfig {
+    pub enabled: bool,
+    pub port: u16,
+    pub lan: bool,
+    /// `host:port`
+    pub local_upstream: String,
+    /// `host:port`
+    pub remote_upstream: String,
+}
+
+impl Default for DnsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 10053,
+            lan: false,
+            local_upstream: "114.114.114.114:53".to_string(),
+            remote_upstream: "8.8.8.8:53".to_string(),
+        }
+    }
+}
+
+impl DnsConfig {
+    pub fn address(&self) -> &'static str {
+        if self.lan {
+            "0.0.0.0"
+        } else {
+            "127.0.0.1"
+        }
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.port == 0 {
+            anyhow::bail!("port must be between 1 and 65535");
+        }
+        parse_host_port(&self.local_upstream)?;
+        parse_host_port(&self.remote_upstream)?;
+        Ok(())
+    }
+}
+
+/// Copying sslocal output into rotating files under `logs`.
+#[derive(Serialize, Deserialize)]
+pub struct LogFiles {
+    pub enabled: bool,
+    pub max_size_kb: u64,
+    /// Files kept, the one being written included.
+    pub max_files: usize,
+}
+
+impl Default for LogFiles {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_size_kb: 1024,
+            max_files: 5,
+        }
+    }
+}
+
+fn default_probe_url() -> String {
+    "http://www.gstatic.com/generate_204".to_string()
 }
 
 impl Default for UserData {
@@ -17,23 +198,111 @@ impl Default for UserData {
             lan_support: false,
             selected_server: None,
             server_groups: Vec::new(),
+            probe_url: default_probe_url(),
+            health_check: HealthCheck::default(),
+            max_restarts: default_max_restarts(),
+            log_files: LogFiles::default(),
+            listeners: Vec::new(),
+            dns: DnsConfig::default(),
+            acl: None,
         }
     }
 }
 
+/// A file or directory next to the executable, where `userdata` lives.
+pub fn data_path(name: &str) -> io::Result<PathBuf> {
+    let mut path = current_exe()?;
+    path.set_file_name(name);
+    Ok(path)
+}
+
 impl UserData {
+    /// Every listener, the main SOCKS5 one first.
+    pub fn all_listeners(&self) -> Vec<Listener> {
+        let mut listeners = vec![Listener {
+            protocol: ListenerProtocol::Socks,
+            port: self.local_port,
+            lan: self.lan_support,
+            tproxy: false,
+        }];
+        listeners.extend(self.listeners.iter().cloned());
+        listeners
+    }
+
+    pub fn set_listeners(&mut self, mut listeners: Vec<Listener>) {
+        if listeners.is_empty() {
+            return;
+        }
+        let main = listeners.remove(0);
+        self.local_port = main.port;
+        self.lan_support = main.lan;
+        self.listeners = listeners;
+    }
+
+    /// The ACL rule set name for servers of a group.
+    pub fn acl_for(&self, group_index: usize) -> Option<&str> {
+        self.server_groups[group_index]
+            .acl
+            .as_deref()
+            .or(self.acl.as_deref())
+    }
+
     pub fn load() -> io::Result<Self> {
-        let mut file_path = current_exe()?;
-        file_path.set_file_name("userdata");
-        let content = fs::read(file_path)?;
+        let content = fs::read(data_path("userdata")?)?;
         Ok(serde_json::from_slice(&content)?)
     }
 
     pub fn save(&self) -> io::Result<()> {
-        let mut file_path = current_exe()?;
-        file_path.set_file_name("userdata");
         let content = serde_json::to_vec(&self)?;
-        fs::write(file_path, content)
+        fs::write(data_path("userdata")?, content)
+    }
+
+    /// Removes a server, keeping `selected_server` pointing at the same entry.
+    pub fn remove_server(&mut self, group_index: usize, server_index: usize) -> SSServer {
+        if let Some((i, selected)) = self.selected_server {
+            if i == group_index {
+                self.selected_server = match selected.cmp(&server_index) {
+                    Ordering::Less => Some((i, selected)),
+                    Ordering::Equal => None,
+                    Ordering::Greater => Some((i, selected - 1)),
+                };
+            }
+        }
+        self.server_groups[group_index]
+            .ss_servers
+            .remove(server_index)
+    }
+
+    /// Removes a group, keeping `selected_server` pointing at the same entry.
+    pub fn remove_group(&mut self, group_index: usize) -> ServerGroup {
+        if let Some((i, selected)) = self.selected_server {
+            self.selected_server = match i.cmp(&group_index) {
+                Ordering::Less => Some((i, selected)),
+                Ordering::Equal => None,
+                Ordering::Greater => Some((i - 1, selected)),
+            };
+        }
+        self.server_groups.remove(group_index)
+    }
+
+    /// Refreshes a server group, following the selected server to its new position.
+    pub fn update_group(&mut self, group_index: usize) -> anyhow::Result<Vec<String>> {
+        let group = &mut self.server_groups[group_index];
+        let selected = match self.selected_server {
+            Some((i, server_index)) if i == group_index => {
+                group.ss_servers.get(server_index).cloned()
+            }
+            _ => None,
+        };
+        let warnings = group.update()?;
+        if let Some(selected) = selected {
+            self.selected_server = group
+                .ss_servers
+                .iter()
+                .position(|server| server.is_same(&selected))
+                .map(|server_index| (group_index, server_index));
+        }
+        Ok(warnings)
     }
 }
 
@@ -43,6 +312,13 @@ pub struct ServerGroup {
     pub update_url: String,
     pub update_type: Option<ServerUpdateType>,
     pub ss_servers: Vec<SSServer>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bytes_used: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bytes_remaining: Option<u64>,
+    /// ACL rule set overriding the global one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub acl: Option<String>,
 }
 
 impl ServerGroup {
@@ -52,10 +328,118 @@ impl ServerGroup {
             update_url: update_url.into(),
             update_type: None,
             ss_servers: Vec::new(),
+            bytes_used: None,
+            bytes_remaining: None,
+            acl: None,
         }
     }
 
+    /// A group of hand-entered servers that is never refreshed.
+    pub fn manual<S: Into<String>>(name: S) -> Self {
+        Self {
+            update_type: Some(ServerUpdateType::Manual),
+            ..Self::new(name.into(), String::new())
+        }
+    }
+
+    pub fn is_manual(&self) -> bool {
+        matches!(self.update_type, Some(ServerUpdateType::Manual))
+    }
+
+    /// The server to fail over to from `current`: the fastest by last measured
+    /// latency, skipping known dead ones, then the next in order.
+    pub fn next_best_server(&self, current: usize) -> Option<usize> {
+        let len = self.ss_servers.len();
+        let candidates = (1..len).map(|offset| (current + offset) % len);
+        candidates
+            .clone()
+            .filter(|&i| {
+                !self.ss_servers[i]
+                    .latency
+                    .as_ref()
+                    .is_some_and(Latency::is_failure)
+            })
+            .min_by_key(|&i| {
+                self.ss_servers[i]
+                    .latency
+                    .as_ref()
+                    .and_then(Latency::as_duration)
+                    .unwrap_or(Duration::MAX)
+            })
+            .or_else(|| candidates.clone().next())
+    }
+
+    /// Base64 subscription content, readable as `ServerUpdateType::SSUrl`.
+    pub fn to_subscription(&self) -> String {
+        let lines: Vec<String> = self.ss_servers.iter().map(SSServer::to_ssurl).collect();
+        BASE64_STANDARD.encode(lines.join("\n"))
+    }
+
+    /// Writes the subscription to `exports/<name>.txt` and returns its path.
+    pub fn export(&self) -> io::Result<PathBuf> {
+        let dir = data_path("exports")?;
+        fs::create_dir_all(&dir)?;
+        let file_name: String = self
+            .name
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let path = dir.join(format!("{}.txt", file_name));
+        fs::write(&path, self.to_subscription())?;
+        Ok(path)
+    }
+
+    /// The file behind a `file://` url or plain path, `None` for http(s) urls.
+    fn local_path(&self) -> anyhow::Result<Option<PathBuf>> {
+        let url = self.update_url.trim();
+        if url.starts_with("http://") || url.starts_with("https://") {
+            return Ok(None);
+        }
+        let Some(rest) = url.strip_prefix("file://") else {
+            return Ok(Some(PathBuf::from(url)));
+        };
+        let (host, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+        let path = match host {
+            "" | "localhost" => urlencoding::decode(path)?,
+            // file://C:/path
+            _ if host.len() == 2 && host.ends_with(':') => urlencoding::decode(rest)?,
+            _ => anyhow::bail!(
+                "'{}' is on host '{}', only local files are supported",
+                url,
+                host
+            ),
+        };
+        // file:///C:/path on windows
+        let path = match path.strip_prefix('/') {
+            Some(rest) if rest.as_bytes().get(1) == Some(&b':') => rest,
+            _ => &path,
+        };
+        Ok(Some(PathBuf::from(path)))
+    }
+
     fn type_check(&mut self, content: &str) -> anyhow::Result<()> {
+        let content = content.trim();
+
+        // sip008
+        if content.starts_with('{') && serde_json::from_str::<Sip008>(content).is_ok() {
+            self.update_type = Some(ServerUpdateType::Sip008);
+            return Ok(());
+        }
+
+        // shadowsocks-rust config.json
+        if content.starts_with('{')
+            && serde_json::from_str::<SSConfig>(content).is_ok_and(|config| !config.is_empty())
+        {
+            self.update_type = Some(ServerUpdateType::SSConfig);
+            return Ok(());
+        }
+
         // ssjson
         if content.starts_with(['[', '{']) && content.ends_with([']', '}']) {
             self.update_type = Some(ServerUpdateType::SSJson);
@@ -63,47 +447,123 @@ impl ServerGroup {
         }
 
         // base64 + ssurl
-        if let Ok(bytes) = BASE64_STANDARD.decode(&content) {
+        if let Ok(bytes) = decode_base64(content) {
             let content = String::from_utf8_lossy(&bytes);
-            if content.starts_with("ss://") {
+            if content.trim_start().starts_with("ss://") {
                 self.update_type = Some(ServerUpdateType::SSUrl);
                 return Ok(());
             }
         }
 
+        // clash yaml
+        if let Ok(clash) = serde_yaml::from_str::<ClashConfig>(content) {
+            if !clash.proxies.is_empty() {
+                self.update_type = Some(ServerUpdateType::Clash);
+                return Ok(());
+            }
+        }
+
+        // plain ssurl lines
+        if parse_ssurl_lines(content)
+            .iter()
+            .any(|(_, result)| result.is_ok())
+        {
+            self.update_type = Some(ServerUpdateType::SSUrl);
+            return Ok(());
+        }
+
         anyhow::bail!("unknown content type");
     }
 
-    fn _update(&mut self, agent: ureq::Agent) -> anyhow::Result<()> {
-        let content = agent.get(&self.update_url).call()?.into_string()?;
+    /// Returns a warning for every entry that was rejected.
+    fn _update(&mut self, agent: ureq::Agent) -> anyhow::Result<Vec<String>> {
+        if self.is_manual() {
+            anyhow::bail!("'{}' is a manual group and has no update source", self.name);
+        }
+
+        let content = match self.local_path()? {
+            Some(path) => fs::read_to_string(&path)
+                .map_err(|err| anyhow::anyhow!("failed to read '{}': {}", path.display(), err))?,
+            None => agent.get(&self.update_url).call()?.into_string()?,
+        };
+        self.load(&content)
+    }
 
+    /// Replaces the servers with the ones in `content`, detecting its format
+    /// on first use.
+    fn load(&mut self, content: &str) -> anyhow::Result<Vec<String>> {
         if self.update_type.is_none() {
-            self.type_check(&content)?;
+            self.type_check(content)?;
         }
 
+        let mut warnings = Vec::new();
         match self.update_type.as_ref().unwrap() {
-            ServerUpdateType::SSJson => self.ss_servers = serde_json::from_str(&content)?,
+            ServerUpdateType::SSJson => self.ss_servers = serde_json::from_str(content)?,
+            ServerUpdateType::Sip008 => {
+                let sip008: Sip008 = serde_json::from_str(content)?;
+                if sip008.version != 1 {
+                    anyhow::bail!("unsupported sip008 version {}", sip008.version);
+                }
+                self.ss_servers = sip008.servers;
+                self.bytes_used = sip008.bytes_used;
+                self.bytes_remaining = sip008.bytes_remaining;
+            }
             ServerUpdateType::SSUrl => {
-                let bytes = BASE64_STANDARD.decode(&content)?;
-                let content = String::from_utf8_lossy(&bytes);
                 let mut ss_servers = Vec::new();
-                for line in content.lines() {
-                    if let Ok(ss_server) = SSServer::from_ssurl_str(line) {
-                        ss_servers.push(ss_server);
+                for (line, result) in parse_ssurl_lines(content) {
+                    match result {
+                        Ok(ss_server) => ss_servers.push(ss_server),
+                        Err(err) => warnings.push(format!("line {}: {}", line, err)),
+                    }
+                }
+                self.ss_servers = ss_servers;
+            }
+            ServerUpdateType::SSConfig => {
+                let config: SSConfig = serde_json::from_str(content)?;
+                if config.is_empty() {
+                    anyhow::bail!("no servers in '{}'", self.update_url);
+                }
+                self.ss_servers = config.into_servers();
+            }
+            ServerUpdateType::Manual => unreachable!(),
+            ServerUpdateType::Clash => {
+                let clash: ClashConfig = serde_yaml::from_str(content)?;
+                let mut ss_servers = Vec::new();
+                let mut skipped: BTreeMap<String, usize> = BTreeMap::new();
+                for proxy in &clash.proxies {
+                    let proxy_type = yaml_string(proxy.get("type")).unwrap_or_default();
+                    if proxy_type != "ss" {
+                        *skipped.entry(proxy_type).or_default() += 1;
+                        continue;
                     }
+                    match SSServer::from_clash_proxy(proxy) {
+                        Ok(ss_server) => ss_servers.push(ss_server),
+                        Err(err) => warnings.push(format!(
+                            "proxy '{}': {}",
+                            yaml_string(proxy.get("name")).unwrap_or_default(),
+                            err
+                        )),
+                    }
+                }
+                for (proxy_type, count) in skipped {
+                    warnings.push(format!(
+                        "skipped {} unsupported '{}' proxies",
+                        count, proxy_type
+                    ));
                 }
                 self.ss_servers = ss_servers;
             }
         }
-        Ok(())
+        Ok(warnings)
     }
 
-    pub fn update(&mut self) -> anyhow::Result<()> {
+    pub fn update(&mut self) -> anyhow::Result<Vec<String>> {
         let agent = ureq::AgentBuilder::new().build();
         self._update(agent)
     }
 
-    pub fn update_proxy<P: AsRef<str>>(&mut self, proxy: P) -> anyhow::Result<()> {
+    #[allow(dead_code)]
+    pub fn update_proxy<P: AsRef<str>>(&mut self, proxy: P) -> anyhow::Result<Vec<String>> {
         let proxy = ureq::Proxy::new(proxy).unwrap();
         let agent = ureq::AgentBuilder::new().proxy(proxy).build();
         self._update(agent)
@@ -114,44 +574,886 @@ impl ServerGroup {
 pub enum ServerUpdateType {
     SSJson,
     SSUrl,
+    Sip008,
+    Clash,
+    SSConfig,
+    Manual,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// Ciphers accepted by shadowsocks-rust.
+pub const METHODS: &[&str] = &[
+    "aes-128-gcm",
+    "aes-256-gcm",
+    "chacha20-ietf-poly1305",
+    "2022-blake3-aes-128-gcm",
+    "2022-blake3-aes-256-gcm",
+    "2022-blake3-chacha20-poly1305",
+    "2022-blake3-chacha8-poly1305",
+    "aes-128-ccm",
+    "aes-256-ccm",
+    "aes-128-gcm-siv",
+    "aes-256-gcm-siv",
+    "xchacha20-ietf-poly1305",
+    "sm4-gcm",
+    "sm4-ccm",
+    "aes-128-cfb",
+    "aes-192-cfb",
+    "aes-256-cfb",
+    "aes-128-ctr",
+    "aes-192-ctr",
+    "aes-256-ctr",
+    "camellia-128-cfb",
+    "camellia-192-cfb",
+    "camellia-256-cfb",
+    "chacha20-ietf",
+    "rc4-md5",
+    "plain",
+    "none",
+];
+
+/// SIP008 online configuration delivery document.
+#[derive(Deserialize)]
+struct Sip008 {
+    version: u32,
+    servers: Vec<SSServer>,
+    bytes_used: Option<u64>,
+    bytes_remaining: Option<u64>,
+}
+
+/// shadowsocks-rust `config.json`, either a single server at the top level or
+/// a `servers` array.
+#[derive(Deserialize)]
+struct SSConfig {
+    #[serde(flatten)]
+    server: Option<SSConfigServer>,
+    #[serde(default)]
+    servers: Vec<SSConfigServer>,
+}
+
+/// A server as written in `config.json`, which also accepts `address` and
+/// `port` for the server fields.
+#[derive(Deserialize)]
+struct SSConfigServer {
+    #[serde(default)]
+    remarks: String,
+    #[serde(alias = "address")]
+    server: String,
+    #[serde(alias = "port")]
+    server_port: u16,
+    method: String,
+    password: String,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    plugin: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    plugin_opts: Option<String>,
+    #[serde(default)]
+    disabled: bool,
+}
+
+impl From<SSConfigServer> for SSServer {
+    fn from(server: SSConfigServer) -> Self {
+        SSServer {
+            id: None,
+            remarks: server.remarks,
+            server: server.server,
+            server_port: server.server_port,
+            method: server.method,
+            password: server.password,
+            plugin: server.plugin,
+            plugin_opts: server.plugin_opts,
+            latency: None,
+        }
+    }
+}
+
+impl SSConfig {
+    fn is_empty(&self) -> bool {
+        self.server.is_none() && self.servers.is_empty()
+    }
+
+    fn into_servers(self) -> Vec<SSServer> {
+        self.server
+            .into_iter()
+            .chain(self.servers)
+            .filter(|server| !server.disabled)
+            .map(SSServer::from)
+            .collect()
+    }
+}
+
+/// The part of a Clash profile we care about.
+#[derive(Deserialize)]
+struct ClashConfig {
+    #[serde(default)]
+    proxies: Vec<serde_yaml::Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SSServer {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default)]
     pub remarks: String,
     pub server: String,
     pub server_port: u16,
     pub method: String,
     pub password: String,
+    #[serde(
+        default,
+        deserialize_with = "empty_as_none",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub plugin: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "empty_as_none",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub plugin_opts: Option<String>,
+    #[serde(skip)]
+    pub latency: Option<Latency>,
 }
 
 impl SSServer {
-    fn from_ssurl_str(ssurl: &str) -> anyhow::Result<Self> {
-        if ssurl.starts_with("ss://") {
-            let url = urlencoding::decode(ssurl)?;
-            let i = url.find('#').unwrap_or(url.len());
-            let bytes = BASE64_STANDARD_NO_PAD.decode(&url[5..i])?;
-            let content = String::from_utf8_lossy(&bytes);
-            let parts: Vec<_> = content.split([':', '@']).collect();
-            let remarks = if i < url.len() {
-                url[i + 1..].to_string()
-            } else {
-                String::new()
-            };
-            if parts.len() == 4 {
-                let method = parts[0].to_string();
-                let password = parts[1].to_string();
-                let server = parts[2].to_string();
-                let server_port = parts[3].parse()?;
-                return Ok(SSServer {
-                    remarks,
+    pub fn from_ssurl_str(ssurl: &str) -> anyhow::Result<Self> {
+        let Some(body) = ssurl.trim().strip_prefix("ss://") else {
+            anyhow::bail!("not an ss:// url");
+        };
+        let (body, remarks) = match body.split_once('#') {
+            Some((body, tag)) => (body, urlencoding::decode(tag)?.into_owned()),
+            None => (body, String::new()),
+        };
+
+        // the query may carry an '@' of its own, so drop it before looking
+        // for the userinfo
+        let (body, query) = match body.split_once('?') {
+            Some((body, query)) => (body.trim_end_matches('/'), query),
+            None => (body.trim_end_matches('/'), ""),
+        };
+        let mut plugin = None;
+        let mut plugin_opts = None;
+        for (key, value) in parse_query(query)? {
+            if key == "plugin" && !value.is_empty() {
+                // plugin=name;opt1=value1;opt2=value2
+                match value.split_once(';') {
+                    Some((name, opts)) => {
+                        plugin = Some(name.to_string());
+                        plugin_opts = Some(opts.to_string());
+                    }
+                    None => plugin = Some(value),
+                }
+            }
+        }
+
+        let (method, password, server, server_port) = match body.rsplit_once('@') {
+            // SIP002: ss://userinfo@host:port/?query
+            Some((userinfo, host_port)) => {
+                let (method, password) = parse_userinfo(userinfo)?;
+                let (server, server_port) = parse_host_port(host_port)?;
+                (method, password, server, server_port)
+            }
+            // legacy: ss://BASE64(method:password@host:port)
+            None => {
+                let bytes = decode_base64(&urlencoding::decode(body)?)
+                    .map_err(|err| anyhow::anyhow!("invalid base64: {}", err))?;
+                let content = String::from_utf8(bytes)?;
+                let Some((userinfo, host_port)) = content.rsplit_once('@') else {
+                    anyhow::bail!("missing '@' in '{}'", content);
+                };
+                let Some((method, password)) = userinfo.split_once(':') else {
+                    anyhow::bail!("missing password in '{}'", content);
+                };
+                let (server, server_port) = parse_host_port(host_port)?;
+                (
+                    method.to_string(),
+                    password.to_string(),
                     server,
                     server_port,
-                    method,
-                    password,
-                });
+                )
             }
+        };
+
+        if method.is_empty() {
+            anyhow::bail!("empty method");
         }
-        anyhow::bail!("invalid ssurl: {}", ssurl);
+        Ok(SSServer {
+            id: None,
+            remarks,
+            server,
+            server_port,
+            method,
+            password,
+            plugin,
+            plugin_opts,
+            latency: None,
+        })
+    }
+
+    fn from_clash_proxy(proxy: &serde_yaml::Value) -> anyhow::Result<Self> {
+        let field = |name: &str| {
+            yaml_string(proxy.get(name)).ok_or_else(|| anyhow::anyhow!("missing '{}'", name))
+        };
+        let port = field("port")?;
+        let server_port = match port.parse::<u16>() {
+            Ok(port) if port != 0 => port,
+            _ => anyhow::bail!("invalid port '{}'", port),
+        };
+
+        let plugin = yaml_string(proxy.get("plugin")).filter(|plugin| !plugin.is_empty());
+        let (plugin, plugin_opts) = match plugin {
+            None => (None, None),
+            Some(plugin) => {
+                let opts = proxy.get("plugin-opts");
+                let opt = |name: &str| yaml_string(opts.and_then(|opts| opts.get(name)));
+                let mut plugin_opts = Vec::new();
+                match plugin.as_str() {
+                    "obfs" => {
+                        if let Some(mode) = opt("mode") {
+                            plugin_opts.push(format!("obfs={}", mode));
+                        }
+                        if let Some(host) = opt("host") {
+                            plugin_opts.push(format!("obfs-host={}", host));
+                        }
+                        (Some("obfs-local".to_string()), Some(plugin_opts.join(";")))
+                    }
+                    "v2ray-plugin" => {
+                        if let Some(mode) = opt("mode").filter(|mode| mode != "websocket") {
+                            plugin_opts.push(format!("mode={}", mode));
+                        }
+                        if opt("tls").is_some_and(|tls| tls == "true") {
+                            plugin_opts.push("tls".to_string());
+                        }
+                        if let Some(host) = opt("host") {
+                            plugin_opts.push(format!("host={}", host));
+                        }
+                        if let Some(path) = opt("path") {
+                            plugin_opts.push(format!("path={}", path));
+                        }
+                        (Some(plugin), Some(plugin_opts.join(";")))
+                    }
+                    _ => anyhow::bail!("plugin '{}' is not supported", plugin),
+                }
+            }
+        };
+
+        Ok(SSServer {
+            id: None,
+            remarks: field("name")?,
+            server: field("server")?,
+            server_port,
+            method: field("cipher")?,
+            password: field("password")?,
+            plugin,
+            plugin_opts: plugin_opts.filter(|opts| !opts.is_empty()),
+            latency: None,
+        })
+    }
+
+    /// SIP002 share link.
+    pub fn to_ssurl(&self) -> String {
+        let userinfo = if self.method.starts_with("2022-") {
+            // 2022 keys are already base64, SIP002 wants them percent-encoded
+            format!(
+                "{}:{}",
+                urlencoding::encode(&self.method),
+                urlencoding::encode(&self.password)
+            )
+        } else {
+            BASE64_URL_SAFE_NO_PAD.encode(format!("{}:{}", self.method, self.password))
+        };
+        let mut url = format!("ss://{}@{}", userinfo, self.address());
+        if let Some(plugin) = &self.plugin {
+            let plugin = match &self.plugin_opts {
+                Some(plugin_opts) => format!("{};{}", plugin, plugin_opts),
+                None => plugin.clone(),
+            };
+            url.push_str("/?plugin=");
+            url.push_str(&urlencoding::encode(&plugin));
+        }
+        if !self.remarks.is_empty() {
+            url.push('#');
+            url.push_str(&urlencoding::encode(&self.remarks));
+        }
+        url
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.server.trim().is_empty() {
+            anyhow::bail!("server is empty");
+        }
+        if self.server_port == 0 {
+            anyhow::bail!("port must be between 1 and 65535");
+        }
+        if !METHODS.contains(&self.method.as_str()) {
+            anyhow::bail!("unknown method '{}'", self.method);
+        }
+        if self.password.is_empty() && !matches!(self.method.as_str(), "plain" | "none") {
+            anyhow::bail!("password is empty");
+        }
+        Ok(())
+    }
+
+    /// Matches by SIP008 id when both have one, by address otherwise.
+    pub fn is_same(&self, other: &SSServer) -> bool {
+        match (&self.id, &other.id) {
+            (Some(id), Some(other_id)) => id == other_id,
+            _ => self.server == other.server && self.server_port == other.server_port,
+        }
+    }
+
+    /// `host:port`, with IPv6 hosts wrapped in brackets.
+    pub fn address(&self) -> String {
+        if self.server.contains(':') {
+            format!("[{}]:{}", self.server, self.server_port)
+        } else {
+            format!("{}:{}", self.server, self.server_port)
+        }
+    }
+}
+
+/// Parses one `ss://` link per non-empty line, unwrapping base64 encoded text
+/// first. Each result carries its 1-based line number.
+pub fn parse_ssurl_lines(content: &str) -> Vec<(usize, anyhow::Result<SSServer>)> {
+    let trimmed = content.trim();
+    if !trimmed.is_empty() && !trimmed.starts_with("ss://") {
+        if let Some(decoded) = decode_base64(trimmed)
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .filter(|decoded| decoded.trim_start().starts_with("ss://"))
+        {
+            return parse_ssurl_lines(&decoded);
+        }
+    }
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| (i + 1, SSServer::from_ssurl_str(line)))
+        .collect()
+}
+
+/// Decodes standard or URL-safe base64, with or without padding.
+pub fn decode_base64(input: &str) -> Result<Vec<u8>, base64::DecodeError> {
+    let input: String = input
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace())
+        .map(|c| match c {
+            '-' => '+',
+            '_' => '/',
+            c => c,
+        })
+        .collect();
+    BASE64_STANDARD_NO_PAD.decode(input.trim_end_matches('='))
+}
+
+fn parse_userinfo(userinfo: &str) -> anyhow::Result<(String, String)> {
+    let userinfo = urlencoding::decode(userinfo)?;
+    // base64(method:password), or plain method:password for 2022 ciphers
+    let decoded = decode_base64(&userinfo)
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .filter(|decoded| decoded.contains(':'));
+    let userinfo = decoded.unwrap_or_else(|| userinfo.into_owned());
+    match userinfo.split_once(':') {
+        Some((method, password)) => Ok((method.to_string(), password.to_string())),
+        None => anyhow::bail!("invalid userinfo '{}'", userinfo),
+    }
+}
+
+pub fn parse_host_port(host_port: &str) -> anyhow::Result<(String, u16)> {
+    let (host, port) = match host_port.strip_prefix('[') {
+        Some(rest) => match rest.split_once("]:") {
+            Some(parts) => parts,
+            None => anyhow::bail!("invalid ipv6 address '{}'", host_port),
+        },
+        None => match host_port.rsplit_once(':') {
+            Some(parts) => parts,
+            None => anyhow::bail!("missing port in '{}'", host_port),
+        },
+    };
+    if host.is_empty() {
+        anyhow::bail!("missing host in '{}'", host_port);
+    }
+    match port.parse::<u16>() {
+        Ok(port) if port != 0 => Ok((host.to_string(), port)),
+        _ => anyhow::bail!("invalid port '{}'", port),
+    }
+}
+
+/// Subscription converters often write `"plugin": ""` for no plugin.
+fn empty_as_none<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    let value = Option::<String>::deserialize(deserializer)?;
+    Ok(value.filter(|value| !value.is_empty()))
+}
+
+/// Reads a YAML scalar as a string, since Clash profiles often leave ports and
+/// passwords unquoted.
+fn yaml_string(value: Option<&serde_yaml::Value>) -> Option<String> {
+    match value? {
+        serde_yaml::Value::String(s) => Some(s.clone()),
+        serde_yaml::Value::Number(n) => Some(n.to_string()),
+        serde_yaml::Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+fn parse_query(query: &str) -> anyhow::Result<Vec<(String, String)>> {
+    let mut params = Vec::new();
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        params.push((
+            urlencoding::decode(key)?.into_owned(),
+            urlencoding::decode(value)?.into_owned(),
+        ));
+    }
+    Ok(params)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(ssurl: &str) -> SSServer {
+        SSServer::from_ssurl_str(ssurl).unwrap()
+    }
+
+    #[test]
+    fn sip002_url_safe_unpadded() {
+        let server = parse("ss://YWVzLTI1Ni1nY206YT9iPmN-ZA@example.com:8388#Tokyo%201");
+        assert_eq!(server.method, "aes-256-gcm");
+        assert_eq!(server.password, "a?b>c~d");
+        assert_eq!(server.server, "example.com");
+        assert_eq!(server.server_port, 8388);
+        assert_eq!(server.remarks, "Tokyo 1");
+        assert_eq!(server.plugin, None);
+    }
+
+    #[test]
+    fn sip002_standard_padded() {
+        let server = parse("ss://YWVzLTI1Ni1nY206YT9iPmN+ZA==@example.com:8388");
+        assert_eq!(server.method, "aes-256-gcm");
+        assert_eq!(server.password, "a?b>c~d");
+        let server = parse("ss://YWVzLTI1Ni1nY206YT9iPmN%2BZA%3D%3D@example.com:8388/");
+        assert_eq!(server.password, "a?b>c~d");
+    }
+
+    #[test]
+    fn sip002_percent_encoded_2022_userinfo() {
+        let server = parse(
+            "ss://2022-blake3-aes-128-gcm:YctPZ6U7xPPcU%2Bgp3u%2B0tx%2FtRizJN9K8y%2BuKlW2qjlI%3D@[2001:db8::1]:8443",
+        );
+        assert_eq!(server.method, "2022-blake3-aes-128-gcm");
+        assert_eq!(
+            server.password,
+            "YctPZ6U7xPPcU+gp3u+0tx/tRizJN9K8y+uKlW2qjlI="
+        );
+        assert_eq!(server.server, "2001:db8::1");
+        assert_eq!(server.server_port, 8443);
+    }
+
+    #[test]
+    fn sip002_plugin_query() {
+        let server = parse(
+            "ss://YWVzLTI1Ni1nY206dGVzdA@example.com:443/?plugin=obfs-local%3Bobfs%3Dhttp%3Bobfs-host%3Dexample.org&foo=bar#obfs",
+        );
+        assert_eq!(server.plugin.as_deref(), Some("obfs-local"));
+        assert_eq!(
+            server.plugin_opts.as_deref(),
+            Some("obfs=http;obfs-host=example.org")
+        );
+        let server = parse("ss://YWVzLTI1Ni1nY206dGVzdA@example.com:443/?plugin=v2ray-plugin");
+        assert_eq!(server.plugin.as_deref(), Some("v2ray-plugin"));
+        assert_eq!(server.plugin_opts, None);
+        let server = parse("ss://YWVzLTI1Ni1nY206dGVzdA@example.com:443/?plugin=");
+        assert_eq!(server.plugin, None);
+    }
+
+    #[test]
+    fn sip002_at_in_query_and_tag() {
+        let server = parse(
+            "ss://YWVzLTI1Ni1nY206dGVzdA@example.com:443/?plugin=v2ray-plugin;path=/ws@v2;host=cdn.example.org#me@home",
+        );
+        assert_eq!(server.server, "example.com");
+        assert_eq!(server.server_port, 443);
+        assert_eq!(server.password, "test");
+        assert_eq!(server.plugin.as_deref(), Some("v2ray-plugin"));
+        assert_eq!(
+            server.plugin_opts.as_deref(),
+            Some("path=/ws@v2;host=cdn.example.org")
+        );
+        assert_eq!(server.remarks, "me@home");
+        let server = parse(
+            "ss://YWVzLTI1Ni1nY206dGVzdA@example.com:443?plugin=obfs-local%3Bobfs-host%3Da@b",
+        );
+        assert_eq!(server.server, "example.com");
+        assert_eq!(server.plugin_opts.as_deref(), Some("obfs-host=a@b"));
+    }
+
+    #[test]
+    fn legacy_base64_with_at_in_password() {
+        let server = parse("ss://YWVzLTEyOC1nY206cGFzc0B3b3JkQGV4YW1wbGUuY29tOjgzODg=#legacy");
+        assert_eq!(server.method, "aes-128-gcm");
+        assert_eq!(server.password, "pass@word");
+        assert_eq!(server.server, "example.com");
+        assert_eq!(server.server_port, 8388);
+        assert_eq!(server.remarks, "legacy");
+        let server = parse("ss://cmM0LW1kNTpwd0BbOjoxXTo0NDM");
+        assert_eq!(server.password, "pw");
+        assert_eq!(server.server, "::1");
+        assert_eq!(server.server_port, 443);
+    }
+
+    #[test]
+    fn rejects_malformed_urls() {
+        for ssurl in [
+            "http://example.com",
+            "ss://YWVzLTI1Ni1nY206dGVzdA@example.com",
+            "ss://YWVzLTI1Ni1nY206dGVzdA@example.com:0",
+            "ss://YWVzLTI1Ni1nY206dGVzdA@[::1:443",
+            "ss://bm9jb2xvbg@example.com:443",
+            "ss://!!!",
+        ] {
+            assert!(SSServer::from_ssurl_str(ssurl).is_err(), "{}", ssurl);
+        }
+    }
+
+    #[test]
+    fn empty_plugin_is_none() {
+        let server: SSServer = serde_json::from_str(
+            r#"{"server":"example.com","server_port":8388,"method":"aes-256-gcm","password":"test","plugin":"","plugin_opts":""}"#,
+        )
+        .unwrap();
+        assert_eq!(server.plugin, None);
+        assert_eq!(server.plugin_opts, None);
+        let server: SSServer = serde_json::from_str(
+            r#"{"server":"example.com","server_port":8388,"method":"aes-256-gcm","password":"test","plugin":null}"#,
+        )
+        .unwrap();
+        assert_eq!(server.plugin, None);
+    }
+
+    #[test]
+    fn host_port() {
+        assert_eq!(
+            parse_host_port("example.com:80").unwrap(),
+            ("example.com".to_string(), 80)
+        );
+        assert_eq!(
+            parse_host_port("[::1]:53").unwrap(),
+            ("::1".to_string(), 53)
+        );
+        assert!(parse_host_port(":53").is_err());
+        assert!(parse_host_port("example.com:65536").is_err());
+    }
+
+    #[test]
+    fn base64_variants() {
+        for input in ["Pz8-Pn5-", "Pz8+Pn5+", "Pz8+\nPn5+", "YQ", "YQ=="] {
+            assert!(decode_base64(input).is_ok(), "{}", input);
+        }
+        assert_eq!(decode_base64("Pz8-Pn5-").unwrap(), b"??>>~~");
+        assert_eq!(decode_base64("YQ==").unwrap(), b"a");
+    }
+
+    #[test]
+    fn to_ssurl_round_trips() {
+        let servers = [
+            SSServer {
+                id: None,
+                remarks: "a b#c".to_string(),
+                server: "example.com".to_string(),
+                server_port: 8388,
+                method: "aes-256-gcm".to_string(),
+                password: "p@ss:w/rd?".to_string(),
+                plugin: Some("obfs-local".to_string()),
+                plugin_opts: Some("obfs=http;obfs-host=example.org".to_string()),
+                latency: None,
+            },
+            SSServer {
+                id: None,
+                remarks: String::new(),
+                server: "2001:db8::1".to_string(),
+                server_port: 443,
+                method: "2022-blake3-aes-256-gcm".to_string(),
+                password: "YctPZ6U7xPPcU+gp3u+0tx/tRizJN9K8y+uKlW2qjlI=".to_string(),
+                plugin: Some("v2ray-plugin".to_string()),
+                plugin_opts: None,
+                latency: None,
+            },
+        ];
+        for server in servers {
+            let parsed = parse(&server.to_ssurl());
+            assert_eq!(parsed.remarks, server.remarks);
+            assert_eq!(parsed.server, server.server);
+            assert_eq!(parsed.server_port, server.server_port);
+            assert_eq!(parsed.method, server.method);
+            assert_eq!(parsed.password, server.password);
+            assert_eq!(parsed.plugin, server.plugin);
+            assert_eq!(parsed.plugin_opts, server.plugin_opts);
+        }
+    }
+
+    fn ss_config(content: &str) -> Vec<SSServer> {
+        let mut group = ServerGroup::new("config", "config.json");
+        group.type_check(content).unwrap();
+        assert!(matches!(
+            group.update_type,
+            Some(ServerUpdateType::SSConfig)
+        ));
+        serde_json::from_str::<SSConfig>(content)
+            .unwrap()
+            .into_servers()
+    }
+
+    #[test]
+    fn ss_config_single_server() {
+        let servers = ss_config(
+            r#"{"server":"example.com","server_port":8388,"method":"aes-256-gcm","password":"test","local_port":1080}"#,
+        );
+        assert_eq!(servers.len(), 1);
+        assert_eq!(servers[0].server, "example.com");
+        assert_eq!(servers[0].server_port, 8388);
+        assert_eq!(servers[0].method, "aes-256-gcm");
+    }
+
+    #[test]
+    fn ss_config_servers_array() {
+        let servers = ss_config(
+            r#"{"servers":[
+                {"address":"a.example.com","port":8388,"method":"aes-256-gcm","password":"a","remarks":"A"},
+                {"server":"b.example.com","server_port":443,"method":"chacha20-ietf-poly1305","password":"b","plugin":"v2ray-plugin","plugin_opts":"tls"}
+            ]}"#,
+        );
+        assert_eq!(servers.len(), 2);
+        assert_eq!(servers[0].server, "a.example.com");
+        assert_eq!(servers[0].server_port, 8388);
+        assert_eq!(servers[0].remarks, "A");
+        assert_eq!(servers[1].server, "b.example.com");
+        assert_eq!(servers[1].server_port, 443);
+        assert_eq!(servers[1].plugin.as_deref(), Some("v2ray-plugin"));
+        assert_eq!(servers[1].plugin_opts.as_deref(), Some("tls"));
+    }
+
+    #[test]
+    fn ss_config_skips_disabled() {
+        let servers = ss_config(
+            r#"{"servers":[
+                {"address":"a.example.com","port":8388,"method":"aes-256-gcm","password":"a","disabled":true},
+                {"address":"b.example.com","port":8389,"method":"aes-256-gcm","password":"b","disabled":false}
+            ]}"#,
+        );
+        assert_eq!(servers.len(), 1);
+        assert_eq!(servers[0].server, "b.example.com");
+    }
+
+    fn sip008(content: &str) -> anyhow::Result<ServerGroup> {
+        let mut group = ServerGroup::new("sip008", "https://example.com/sip008.json");
+        group.load(content)?;
+        assert!(matches!(group.update_type, Some(ServerUpdateType::Sip008)));
+        Ok(group)
+    }
+
+    #[test]
+    fn sip008_envelope() {
+        let group = sip008(
+            r#"{"version":1,"servers":[
+                {"id":"27b8a625-4f4b-4428-9f0f-8a2317db7c79","remarks":"A","server":"a.example.com","server_port":8388,"method":"aes-256-gcm","password":"a"},
+                {"id":"7842c068-c667-41f2-8f7d-04feece3cb67","remarks":"B","server":"b.example.com","server_port":443,"method":"chacha20-ietf-poly1305","password":"b","plugin":"obfs-local","plugin_opts":"obfs=http"}
+            ],"bytes_used":274877906944,"bytes_remaining":824633720832}"#,
+        )
+        .unwrap();
+        assert_eq!(group.ss_servers.len(), 2);
+        assert_eq!(
+            group.ss_servers[0].id.as_deref(),
+            Some("27b8a625-4f4b-4428-9f0f-8a2317db7c79")
+        );
+        assert_eq!(group.ss_servers[0].remarks, "A");
+        assert_eq!(group.ss_servers[1].server_port, 443);
+        assert_eq!(group.ss_servers[1].plugin.as_deref(), Some("obfs-local"));
+        assert_eq!(
+            group.ss_servers[1].plugin_opts.as_deref(),
+            Some("obfs=http")
+        );
+        assert_eq!(group.bytes_used, Some(274877906944));
+        assert_eq!(group.bytes_remaining, Some(824633720832));
+    }
+
+    #[test]
+    fn sip008_unsupported_version() {
+        let err = sip008(
+            r#"{"version":2,"servers":[{"server":"a.example.com","server_port":8388,"method":"aes-256-gcm","password":"a"}]}"#,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(err.to_string(), "unsupported sip008 version 2");
+    }
+
+    #[test]
+    fn sip008_without_traffic() {
+        let group = sip008(
+            r#"{"version":1,"servers":[{"server":"a.example.com","server_port":8388,"method":"aes-256-gcm","password":"a"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(group.ss_servers.len(), 1);
+        assert_eq!(group.bytes_used, None);
+        assert_eq!(group.bytes_remaining, None);
+    }
+
+    #[test]
+    fn sip008_empty_plugin() {
+        let group = sip008(
+            r#"{"version":1,"servers":[{"server":"a.example.com","server_port":8388,"method":"aes-256-gcm","password":"a","plugin":"","plugin_opts":""}]}"#,
+        )
+        .unwrap();
+        assert_eq!(group.ss_servers[0].plugin, None);
+        assert_eq!(group.ss_servers[0].plugin_opts, None);
+    }
+
+    #[test]
+    fn clash_proxies() {
+        let mut group = ServerGroup::new("clash", "https://example.com/clash.yaml");
+        let warnings = group
+            .load(
+                r#"
+proxies:
+  - name: plain
+    type: ss
+    server: a.example.com
+    port: 8388
+    cipher: aes-256-gcm
+    password: 123456
+  - name: obfs
+    type: ss
+    server: b.example.com
+    port: "443"
+    cipher: chacha20-ietf-poly1305
+    password: b
+    plugin: obfs
+    plugin-opts:
+      mode: tls
+      host: bing.com
+  - name: v2ray
+    type: ss
+    server: c.example.com
+    port: 443
+    cipher: aes-128-gcm
+    password: c
+    plugin: v2ray-plugin
+    plugin-opts:
+      mode: websocket
+      tls: true
+      host: cdn.example.org
+      path: /ws
+  - name: shadow-tls
+    type: ss
+    server: d.example.com
+    port: 443
+    cipher: aes-128-gcm
+    password: d
+    plugin: shadow-tls
+  - name: vmess-1
+    type: vmess
+    server: e.example.com
+    port: 443
+  - name: vmess-2
+    type: vmess
+    server: f.example.com
+    port: 443
+  - name: trojan
+    type: trojan
+    server: g.example.com
+    port: 443
+"#,
+            )
+            .unwrap();
+        assert!(matches!(group.update_type, Some(ServerUpdateType::Clash)));
+
+        let servers = &group.ss_servers;
+        assert_eq!(servers.len(), 3);
+        assert_eq!(servers[0].remarks, "plain");
+        assert_eq!(servers[0].server, "a.example.com");
+        assert_eq!(servers[0].server_port, 8388);
+        assert_eq!(servers[0].method, "aes-256-gcm");
+        assert_eq!(servers[0].password, "123456");
+        assert_eq!(servers[0].plugin, None);
+        assert_eq!(servers[0].plugin_opts, None);
+
+        assert_eq!(servers[1].server_port, 443);
+        assert_eq!(servers[1].plugin.as_deref(), Some("obfs-local"));
+        assert_eq!(
+            servers[1].plugin_opts.as_deref(),
+            Some("obfs=tls;obfs-host=bing.com")
+        );
+
+        assert_eq!(servers[2].plugin.as_deref(), Some("v2ray-plugin"));
+        assert_eq!(
+            servers[2].plugin_opts.as_deref(),
+            Some("tls;host=cdn.example.org;path=/ws")
+        );
+
+        assert_eq!(
+            warnings,
+            [
+                "proxy 'shadow-tls': plugin 'shadow-tls' is not supported",
+                "skipped 1 unsupported 'trojan' proxies",
+                "skipped 2 unsupported 'vmess' proxies",
+            ]
+        );
+    }
+
+    #[test]
+    fn local_path() {
+        let path = |url: &str| ServerGroup::new("local", url).local_path();
+        assert_eq!(path("https://example.com/sub").unwrap(), None);
+        assert_eq!(
+            path("/srv/sub.txt").unwrap(),
+            Some(PathBuf::from("/srv/sub.txt"))
+        );
+        assert_eq!(
+            path("file:///srv/sub.txt").unwrap(),
+            Some(PathBuf::from("/srv/sub.txt"))
+        );
+        assert_eq!(
+            path("file://localhost/srv/sub.txt").unwrap(),
+            Some(PathBuf::from("/srv/sub.txt"))
+        );
+        assert_eq!(
+            path("file:///C:/Users/me/sub.txt").unwrap(),
+            Some(PathBuf::from("C:/Users/me/sub.txt"))
+        );
+        assert_eq!(
+            path("file://C:/Users/me/sub.txt").unwrap(),
+            Some(PathBuf::from("C:/Users/me/sub.txt"))
+        );
+        assert_eq!(
+            path("file:///srv/my%20servers%20%E6%97%A5%E6%9C%AC.txt").unwrap(),
+            Some(PathBuf::from("/srv/my servers 日本.txt"))
+        );
+        assert!(path("file://nas/share/sub.txt").is_err());
+    }
+
+    #[test]
+    fn plain_ssurl_lines() {
+        let mut group = ServerGroup::new("ssurl", "sub.txt");
+        let warnings = group
+            .load(
+                "ss://YWVzLTI1Ni1nY206dGVzdA@a.example.com:8388#A\n\nss://not-a-link\nss://YWVzLTI1Ni1nY206dGVzdA@b.example.com:8389#B\n",
+            )
+            .unwrap();
+        assert!(matches!(group.update_type, Some(ServerUpdateType::SSUrl)));
+        assert_eq!(group.ss_servers.len(), 2);
+        assert_eq!(group.ss_servers[0].server, "a.example.com");
+        assert_eq!(group.ss_servers[1].server, "b.example.com");
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("line 3: "));
+
+        let mut group = ServerGroup::new("ssurl", "sub.txt");
+        group
+            .load(&BASE64_STANDARD.encode("ss://YWVzLTI1Ni1nY206dGVzdA@a.example.com:8388#A\n"))
+            .unwrap();
+        assert!(matches!(group.update_type, Some(ServerUpdateType::SSUrl)));
+        assert_eq!(group.ss_servers.len(), 1);
     }
 }
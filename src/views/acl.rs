@@ -0,0 +1,312 @@
+use ratatui::{
+    crossterm::event::{Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Flex, Layout},
+    style::{palette::tailwind::*, Style, Styled, Stylize},
+    widgets::{Block, HighlightSpacing, Paragraph, Row, Table, TableState},
+};
+
+use super::{
+    acl_edit::AclEditLayer,
+    messagebox::{
+        show_warnings, CancelableMessageBoxLayer, CancelableMessageBoxResult, MessageBoxLayer,
+        YesNoMessageBoxLayer,
+    },
+};
+use crate::{acl::AclRuleSet, Layer};
+
+/// Lists the ACL rule sets and picks the global one and the one of the
+/// current group.
+pub struct AclLayer {
+    exit: bool,
+    rule_sets: Vec<AclRuleSet>,
+    /// Rule sets whose files couldn't be read, they may still be in use.
+    skipped: Vec<String>,
+    group_name: Option<String>,
+    /// Used to download source lists while sslocal runs.
+    proxy: Option<String>,
+    table_state: TableState,
+    selected_style: Style,
+    row_styles: [Style; 2],
+    pub global: Option<String>,
+    pub group: Option<String>,
+    /// Whether anything sslocal uses may have changed.
+    pub changed: bool,
+    /// Whether the rule sets could be listed, `names` means nothing otherwise.
+    pub loaded: bool,
+}
+
+impl AclLayer {
+    pub fn new(
+        global: Option<String>,
+        group: Option<(String, Option<String>)>,
+        proxy: Option<String>,
+    ) -> Self {
+        let (group_name, group) = match group {
+            Some((name, acl)) => (Some(name), acl),
+            None => (None, None),
+        };
+        Self {
+            exit: false,
+            rule_sets: Vec::new(),
+            skipped: Vec::new(),
+            group_name,
+            proxy,
+            table_state: TableState::default().with_selected(0),
+            selected_style: Style::default().fg(BLACK).bg(INDIGO.c400),
+            row_styles: [
+                Style::default().fg(WHITE).bg(GRAY.c950),
+                Style::default().fg(WHITE).bg(GRAY.c900),
+            ],
+            global,
+            group,
+            changed: false,
+            loaded: false,
+        }
+    }
+
+    /// Names of the rule sets that still exist.
+    pub fn names(&self) -> Vec<String> {
+        self.rule_sets
+            .iter()
+            .map(|r| r.name.clone())
+            .chain(self.skipped.iter().cloned())
+            .collect()
+    }
+
+    fn selected(&self) -> Option<&AclRuleSet> {
+        self.table_state
+            .selected()
+            .and_then(|i| self.rule_sets.get(i))
+    }
+
+    fn show_error(message: String) -> std::io::Result<()> {
+        MessageBoxLayer::new("Error", message)
+            .red()
+            .on_gray()
+            .show()?;
+        Ok(())
+    }
+
+    fn reload(&mut self) -> std::io::Result<()> {
+        match AclRuleSet::load_all() {
+            Ok(loaded) => {
+                self.rule_sets = loaded.rule_sets;
+                self.loaded = true;
+                let warnings: Vec<String> = loaded
+                    .skipped
+                    .iter()
+                    .map(|(name, err)| format!("skipped '{}': {}", name, err))
+                    .collect();
+                let skipped: Vec<String> =
+                    loaded.skipped.into_iter().map(|(name, _)| name).collect();
+                // only once, not again after every edit
+                if skipped != self.skipped {
+                    show_warnings(&warnings)?;
+                }
+                self.skipped = skipped;
+            }
+            Err(err) => {
+                self.loaded = false;
+                Self::show_error(err.to_string())?;
+            }
+        }
+        Ok(())
+    }
+
+    fn edit(&mut self, rule_set: Option<AclRuleSet>) -> std::io::Result<()> {
+        let edit = AclEditLayer::new(rule_set.as_ref(), self.names()).show()?;
+        if let Some(rule_set) = edit.result {
+            if let Err(err) = rule_set.save() {
+                Self::show_error(err.to_string())?;
+            }
+            self.changed = true;
+            self.reload()?;
+            let i = self.rule_sets.iter().position(|r| r.name == rule_set.name);
+            self.table_state.select(i);
+        }
+        Ok(())
+    }
+
+    fn delete(&mut self) -> std::io::Result<()> {
+        let Some(rule_set) = self.selected().cloned() else {
+            return Ok(());
+        };
+        let yes_no = YesNoMessageBoxLayer::new("Info", format!("delete ACL '{}' ?", rule_set.name))
+            .red()
+            .on_gray()
+            .show()?;
+        if yes_no.result.is_yes() {
+            if let Err(err) = rule_set.delete() {
+                Self::show_error(err.to_string())?;
+            }
+            for selection in [&mut self.global, &mut self.group] {
+                if selection.as_ref() == Some(&rule_set.name) {
+                    *selection = None;
+                }
+            }
+            self.changed = true;
+            self.reload()?;
+        }
+        Ok(())
+    }
+
+    fn update_source(&mut self) -> std::io::Result<()> {
+        let Some(rule_set) = self.selected().cloned() else {
+            return Ok(());
+        };
+        let proxy = self.proxy.clone();
+        let cancelable = CancelableMessageBoxLayer::new(
+            "Info",
+            format!("download ip list of '{}'...", rule_set.name),
+            std::thread::spawn(move || rule_set.update_source(proxy.as_deref())),
+        )
+        .green()
+        .on_gray()
+        .show()?;
+        if let CancelableMessageBoxResult::Complete(result) = cancelable.result {
+            match result {
+                Ok(count) => {
+                    MessageBoxLayer::new("Info", format!("{} rules downloaded", count))
+                        .green()
+                        .on_gray()
+                        .show()?;
+                    self.changed = true;
+                }
+                Err(err) => Self::show_error(err.to_string())?,
+            }
+        }
+        Ok(())
+    }
+
+    fn toggle(selection: &mut Option<String>, name: &str) {
+        *selection = if selection.as_deref() == Some(name) {
+            None
+        } else {
+            Some(name.to_string())
+        };
+    }
+}
+
+impl Layer for AclLayer {
+    fn before_show(&mut self) -> std::io::Result<()> {
+        self.reload()
+    }
+
+    fn view(&mut self, frame: &mut ratatui::Frame) {
+        let [title_layout, table_layout, footer_layout] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .flex(Flex::Legacy)
+        .areas(frame.area());
+
+        let title = " ACL Rule Sets ";
+        let [title_layout] = Layout::horizontal([Constraint::Length(title.len() as u16 + 2)])
+            .flex(Flex::Center)
+            .areas(title_layout);
+        let title = Paragraph::new(title).centered().block(Block::bordered());
+        frame.render_widget(title, title_layout);
+
+        let header = Row::new(["Name", "Default", "Bypass", "Proxy", "IP List", "Used By"]).white();
+        let rows = self.rule_sets.iter().enumerate().map(|(i, rule_set)| {
+            let default = if rule_set.proxy_all {
+                "proxy"
+            } else {
+                "bypass"
+            };
+            let source = match rule_set.source_url {
+                Some(_) if rule_set.has_source() => "downloaded",
+                Some(_) => "not downloaded",
+                None => "-",
+            };
+            let mut used_by = Vec::new();
+            if self.global.as_ref() == Some(&rule_set.name) {
+                used_by.push("global".to_string());
+            }
+            if let Some(group_name) = &self.group_name {
+                if self.group.as_ref() == Some(&rule_set.name) {
+                    used_by.push(format!("'{}'", group_name));
+                }
+            }
+            Row::new([
+                rule_set.name.clone(),
+                default.to_string(),
+                rule_set.bypass.len().to_string(),
+                rule_set.proxy.len().to_string(),
+                source.to_string(),
+                used_by.join(", "),
+            ])
+            .set_style(self.row_styles[i % 2])
+        });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Fill(1),
+                Constraint::Length(8),
+                Constraint::Length(7),
+                Constraint::Length(6),
+                Constraint::Length(15),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(header)
+        .block(Block::bordered())
+        .highlight_style(self.selected_style)
+        .highlight_spacing(HighlightSpacing::Always);
+        frame.render_stateful_widget(table, table_layout, &mut self.table_state);
+
+        let group = match &self.group_name {
+            Some(name) => format!(" | Use for '{}' (p)", name),
+            None => String::new(),
+        };
+        let footer = Paragraph::new(format!(
+            "↑ | ↓ | Add (a) | Edit (Enter) | Del (Del) | Download IP List (u) | Use Globally (g){} | Exit (Esc)",
+            group
+        ))
+        .centered();
+        frame.render_widget(footer, footer_layout);
+    }
+
+    fn update(&mut self, event: Option<Event>) -> std::io::Result<()> {
+        if let Some(Event::Key(key_event)) = event {
+            if key_event.kind == KeyEventKind::Press {
+                match key_event.code {
+                    KeyCode::Esc => self.exit = true,
+                    KeyCode::Up => self.table_state.select_previous(),
+                    KeyCode::Down => self.table_state.select_next(),
+                    KeyCode::Char('a') => self.edit(None)?,
+                    KeyCode::Enter | KeyCode::Char('e') => {
+                        if let Some(rule_set) = self.selected().cloned() {
+                            self.edit(Some(rule_set))?;
+                        }
+                    }
+                    KeyCode::Delete => self.delete()?,
+                    KeyCode::Char('u') => self.update_source()?,
+                    KeyCode::Char('g') => {
+                        if let Some(name) = self.selected().map(|r| r.name.clone()) {
+                            Self::toggle(&mut self.global, &name);
+                            self.changed = true;
+                        }
+                    }
+                    KeyCode::Char('p') if self.group_name.is_some() => {
+                        if let Some(name) = self.selected().map(|r| r.name.clone()) {
+                            Self::toggle(&mut self.group, &name);
+                            self.changed = true;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn close(&mut self) {
+        self.exit = true;
+    }
+
+    fn is_exit(&self) -> bool {
+        self.exit
+    }
+}
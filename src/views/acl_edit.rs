@@ -0,0 +1,236 @@
+use ratatui::{
+    crossterm::event::{Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Flex, Layout},
+    style::Stylize,
+    widgets::{Block, Paragraph, Wrap},
+};
+
+use super::messagebox::MessageBoxLayer;
+use crate::{acl::AclRuleSet, Layer};
+
+#[derive(Clone, Copy, PartialEq)]
+enum Field {
+    Name,
+    Mode,
+    Bypass,
+    Proxy,
+    SourceUrl,
+}
+
+impl Field {
+    const ALL: [Field; 5] = [
+        Field::Name,
+        Field::Mode,
+        Field::Bypass,
+        Field::Proxy,
+        Field::SourceUrl,
+    ];
+
+    fn title(&self) -> &'static str {
+        match self {
+            Field::Name => "Name",
+            Field::Mode => "Default (← →)",
+            Field::Bypass => "Bypass (IPs, CIDRs or domains, separated by spaces)",
+            Field::Proxy => "Proxy (IPs, CIDRs or domains, separated by spaces)",
+            Field::SourceUrl => "Bypassed IP List URL (optional, downloaded on update)",
+        }
+    }
+
+    fn height(&self) -> u16 {
+        match self {
+            Field::Bypass | Field::Proxy => 6,
+            _ => 3,
+        }
+    }
+
+    fn next(self) -> Self {
+        let i = Field::ALL.iter().position(|f| *f == self).unwrap();
+        Field::ALL[(i + 1) % Field::ALL.len()]
+    }
+
+    fn previous(self) -> Self {
+        let i = Field::ALL.iter().position(|f| *f == self).unwrap();
+        Field::ALL[(i + Field::ALL.len() - 1) % Field::ALL.len()]
+    }
+}
+
+pub struct AclEditLayer {
+    exit: bool,
+    title: String,
+    /// Only a new rule set can be named, the name is its file name.
+    is_new: bool,
+    existing_names: Vec<String>,
+    name: String,
+    proxy_all: bool,
+    bypass: String,
+    proxy: String,
+    source_url: String,
+    field: Field,
+    pub result: Option<AclRuleSet>,
+}
+
+impl AclEditLayer {
+    pub fn new(rule_set: Option<&AclRuleSet>, existing_names: Vec<String>) -> Self {
+        let title = if rule_set.is_some() {
+            " Edit ACL "
+        } else {
+            " Add ACL "
+        };
+        Self {
+            exit: false,
+            title: title.to_string(),
+            is_new: rule_set.is_none(),
+            existing_names,
+            name: rule_set.map(|r| r.name.clone()).unwrap_or_default(),
+            proxy_all: rule_set.is_none_or(|r| r.proxy_all),
+            bypass: rule_set.map(|r| r.bypass.join(" ")).unwrap_or_default(),
+            proxy: rule_set.map(|r| r.proxy.join(" ")).unwrap_or_default(),
+            source_url: rule_set
+                .and_then(|r| r.source_url.clone())
+                .unwrap_or_default(),
+            field: if rule_set.is_some() {
+                Field::Mode
+            } else {
+                Field::Name
+            },
+            result: None,
+        }
+    }
+
+    fn text_mut(&mut self) -> Option<&mut String> {
+        match self.field {
+            Field::Name if self.is_new => Some(&mut self.name),
+            Field::Name | Field::Mode => None,
+            Field::Bypass => Some(&mut self.bypass),
+            Field::Proxy => Some(&mut self.proxy),
+            Field::SourceUrl => Some(&mut self.source_url),
+        }
+    }
+
+    fn value(&self, field: Field) -> String {
+        match field {
+            Field::Name => self.name.clone(),
+            Field::Mode if self.proxy_all => "< proxy all except bypassed >".to_string(),
+            Field::Mode => "< bypass all except proxied >".to_string(),
+            Field::Bypass => self.bypass.clone(),
+            Field::Proxy => self.proxy.clone(),
+            Field::SourceUrl => self.source_url.clone(),
+        }
+    }
+
+    fn build(&self) -> anyhow::Result<AclRuleSet> {
+        let name = self.name.trim();
+        AclRuleSet::validate_name(name)?;
+        if self.is_new && self.existing_names.iter().any(|n| n == name) {
+            anyhow::bail!("'{}' already exists", name);
+        }
+        let rules = |s: &str| s.split_whitespace().map(str::to_string).collect();
+        let source_url = self.source_url.trim();
+        Ok(AclRuleSet {
+            proxy_all: self.proxy_all,
+            bypass: rules(&self.bypass),
+            proxy: rules(&self.proxy),
+            source_url: (!source_url.is_empty()).then(|| source_url.to_string()),
+            ..AclRuleSet::new(name)
+        })
+    }
+}
+
+impl Layer for AclEditLayer {
+    fn before_show(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn view(&mut self, frame: &mut ratatui::Frame) {
+        let mut constraints = vec![Constraint::Length(3)];
+        constraints.extend(Field::ALL.iter().map(|f| Constraint::Length(f.height())));
+        constraints.push(Constraint::Min(0));
+        constraints.push(Constraint::Length(1));
+        let layouts = Layout::vertical(constraints)
+            .flex(Flex::Legacy)
+            .split(frame.area());
+
+        let [title_layout] = Layout::horizontal([Constraint::Length(self.title.len() as u16 + 2)])
+            .flex(Flex::Center)
+            .areas(layouts[0]);
+        let title = Paragraph::new(self.title.as_str())
+            .centered()
+            .block(Block::bordered());
+        frame.render_widget(title, title_layout);
+
+        for (i, field) in Field::ALL.iter().enumerate() {
+            let mut paragraph = Paragraph::new(self.value(*field))
+                .block(Block::bordered().title(field.title()))
+                .wrap(Wrap { trim: true });
+            if *field == self.field {
+                paragraph = paragraph.green();
+            }
+            frame.render_widget(paragraph, layouts[i + 1]);
+        }
+
+        let footer =
+            Paragraph::new("Next (Tab/↓) | Prev (↑) | Clear (Del) | Confirm (Enter) | Exit (Esc)")
+                .centered();
+        frame.render_widget(footer, layouts[layouts.len() - 1]);
+    }
+
+    fn update(&mut self, event: Option<Event>) -> std::io::Result<()> {
+        if let Some(Event::Paste(text)) = &event {
+            if let Some(field) = self.text_mut() {
+                if !field.is_empty() && !field.ends_with(' ') {
+                    field.push(' ');
+                }
+                field.push_str(&text.split_whitespace().collect::<Vec<_>>().join(" "));
+            }
+        }
+        if let Some(Event::Key(key_event)) = event {
+            if key_event.kind == KeyEventKind::Press {
+                match key_event.code {
+                    KeyCode::Esc => self.exit = true,
+                    KeyCode::Tab | KeyCode::Down => self.field = self.field.next(),
+                    KeyCode::BackTab | KeyCode::Up => self.field = self.field.previous(),
+                    KeyCode::Left | KeyCode::Right if self.field == Field::Mode => {
+                        self.proxy_all = !self.proxy_all;
+                    }
+                    KeyCode::Delete => {
+                        if let Some(text) = self.text_mut() {
+                            text.clear();
+                        }
+                    }
+                    KeyCode::Backspace => {
+                        if let Some(text) = self.text_mut() {
+                            text.pop();
+                        }
+                    }
+                    KeyCode::Char(c) => {
+                        if let Some(text) = self.text_mut() {
+                            text.push(c);
+                        }
+                    }
+                    KeyCode::Enter => match self.build() {
+                        Ok(rule_set) => {
+                            self.result = Some(rule_set);
+                            self.exit = true;
+                        }
+                        Err(err) => {
+                            MessageBoxLayer::new("Error", err.to_string())
+                                .red()
+                                .on_gray()
+                                .show()?;
+                        }
+                    },
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn close(&mut self) {
+        self.exit = true;
+    }
+
+    fn is_exit(&self) -> bool {
+        self.exit
+    }
+}
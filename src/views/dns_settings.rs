@@ -0,0 +1,200 @@
+use ratatui::{
+    crossterm::event::{Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Flex, Layout},
+    style::Stylize,
+    widgets::{Block, Paragraph},
+};
+
+use super::messagebox::MessageBoxLayer;
+use crate::{userdata::DnsConfig, Layer};
+
+#[derive(Clone, Copy, PartialEq)]
+enum Field {
+    Enabled,
+    Port,
+    Lan,
+    LocalUpstream,
+    RemoteUpstream,
+}
+
+impl Field {
+    const ALL: [Field; 5] = [
+        Field::Enabled,
+        Field::Port,
+        Field::Lan,
+        Field::LocalUpstream,
+        Field::RemoteUpstream,
+    ];
+
+    fn title(&self) -> &'static str {
+        match self {
+            Field::Enabled => "Enabled (Space)",
+            Field::Port => "Port (avoid 5353, mDNS usually holds it)",
+            Field::Lan => "Listen on LAN (Space)",
+            Field::LocalUpstream => "Local Upstream (host:port, for bypassed names)",
+            Field::RemoteUpstream => "Remote Upstream (host:port, through the server)",
+        }
+    }
+
+    fn next(self) -> Self {
+        let i = Field::ALL.iter().position(|f| *f == self).unwrap();
+        Field::ALL[(i + 1) % Field::ALL.len()]
+    }
+
+    fn previous(self) -> Self {
+        let i = Field::ALL.iter().position(|f| *f == self).unwrap();
+        Field::ALL[(i + Field::ALL.len() - 1) % Field::ALL.len()]
+    }
+}
+
+pub struct DnsSettingsLayer {
+    exit: bool,
+    enabled: bool,
+    port: String,
+    lan: bool,
+    local_upstream: String,
+    remote_upstream: String,
+    field: Field,
+    pub result: Option<DnsConfig>,
+}
+
+impl DnsSettingsLayer {
+    pub fn new(dns: &DnsConfig) -> Self {
+        Self {
+            exit: false,
+            enabled: dns.enabled,
+            port: dns.port.to_string(),
+            lan: dns.lan,
+            local_upstream: dns.local_upstream.clone(),
+            remote_upstream: dns.remote_upstream.clone(),
+            field: Field::Enabled,
+            result: None,
+        }
+    }
+
+    fn text_mut(&mut self) -> Option<&mut String> {
+        match self.field {
+            Field::Port => Some(&mut self.port),
+            Field::LocalUpstream => Some(&mut self.local_upstream),
+            Field::RemoteUpstream => Some(&mut self.remote_upstream),
+            Field::Enabled | Field::Lan => None,
+        }
+    }
+
+    fn value(&self, field: Field) -> String {
+        let yes_no = |b: bool| if b { "yes" } else { "no" }.to_string();
+        match field {
+            Field::Enabled => yes_no(self.enabled),
+            Field::Port => self.port.clone(),
+            Field::Lan => yes_no(self.lan),
+            Field::LocalUpstream => self.local_upstream.clone(),
+            Field::RemoteUpstream => self.remote_upstream.clone(),
+        }
+    }
+
+    fn build(&self) -> anyhow::Result<DnsConfig> {
+        let port = match self.port.trim().parse::<u16>() {
+            Ok(port) => port,
+            Err(_) => anyhow::bail!("port must be between 1 and 65535"),
+        };
+        let dns = DnsConfig {
+            enabled: self.enabled,
+            port,
+            lan: self.lan,
+            local_upstream: self.local_upstream.trim().to_string(),
+            remote_upstream: self.remote_upstream.trim().to_string(),
+        };
+        dns.validate()?;
+        Ok(dns)
+    }
+}
+
+impl Layer for DnsSettingsLayer {
+    fn before_show(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn view(&mut self, frame: &mut ratatui::Frame) {
+        let mut constraints = vec![Constraint::Length(3)];
+        constraints.extend(Field::ALL.iter().map(|_| Constraint::Length(3)));
+        constraints.push(Constraint::Min(0));
+        constraints.push(Constraint::Length(1));
+        let layouts = Layout::vertical(constraints)
+            .flex(Flex::Legacy)
+            .split(frame.area());
+
+        let title = " DNS Settings ";
+        let [title_layout] = Layout::horizontal([Constraint::Length(title.len() as u16 + 2)])
+            .flex(Flex::Center)
+            .areas(layouts[0]);
+        let title = Paragraph::new(title).centered().block(Block::bordered());
+        frame.render_widget(title, title_layout);
+
+        for (i, field) in Field::ALL.iter().enumerate() {
+            let mut paragraph =
+                Paragraph::new(self.value(*field)).block(Block::bordered().title(field.title()));
+            if *field == self.field {
+                paragraph = paragraph.green();
+            }
+            frame.render_widget(paragraph, layouts[i + 1]);
+        }
+
+        let footer =
+            Paragraph::new("Next (Tab/↓) | Prev (↑) | Clear (Del) | Confirm (Enter) | Exit (Esc)")
+                .centered();
+        frame.render_widget(footer, layouts[layouts.len() - 1]);
+    }
+
+    fn update(&mut self, event: Option<Event>) -> std::io::Result<()> {
+        if let Some(Event::Key(key_event)) = event {
+            if key_event.kind == KeyEventKind::Press {
+                match key_event.code {
+                    KeyCode::Esc => self.exit = true,
+                    KeyCode::Tab | KeyCode::Down => self.field = self.field.next(),
+                    KeyCode::BackTab | KeyCode::Up => self.field = self.field.previous(),
+                    KeyCode::Char(' ') if self.field == Field::Enabled => {
+                        self.enabled = !self.enabled;
+                    }
+                    KeyCode::Char(' ') if self.field == Field::Lan => self.lan = !self.lan,
+                    KeyCode::Delete => {
+                        if let Some(text) = self.text_mut() {
+                            text.clear();
+                        }
+                    }
+                    KeyCode::Backspace => {
+                        if let Some(text) = self.text_mut() {
+                            text.pop();
+                        }
+                    }
+                    KeyCode::Char(c) => {
+                        if let Some(text) = self.text_mut() {
+                            text.push(c);
+                        }
+                    }
+                    KeyCode::Enter => match self.build() {
+                        Ok(dns) => {
+                            self.result = Some(dns);
+                            self.exit = true;
+                        }
+                        Err(err) => {
+                            MessageBoxLayer::new("Error", err.to_string())
+                                .red()
+                                .on_gray()
+                                .show()?;
+                        }
+                    },
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn close(&mut self) {
+        self.exit = true;
+    }
+
+    fn is_exit(&self) -> bool {
+        self.exit
+    }
+}
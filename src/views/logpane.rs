@@ -0,0 +1,261 @@
+use std::sync::{Arc, RwLock};
+
+use ratatui::{
+    crossterm::event::{KeyCode, KeyEvent},
+    layout::Rect,
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Paragraph},
+    Frame,
+};
+
+use crate::logbuffer::{LogBuffer, LogLevel, LogLine};
+
+/// Scrollable view over the sslocal log buffer, embedded in the main layer.
+pub struct LogPane {
+    buffer: Arc<RwLock<LogBuffer>>,
+    /// Stick to the newest lines, otherwise `top` is the first line shown.
+    follow: bool,
+    top: u64,
+    height: usize,
+    /// Most verbose level shown.
+    level: LogLevel,
+    query: String,
+    searching: bool,
+    current_match: Option<u64>,
+}
+
+impl LogPane {
+    pub fn new(buffer: Arc<RwLock<LogBuffer>>) -> Self {
+        Self {
+            buffer,
+            follow: true,
+            top: 0,
+            height: 0,
+            level: LogLevel::Trace,
+            query: String::new(),
+            searching: false,
+            current_match: None,
+        }
+    }
+
+    /// Every key of the log pane state, for the help popup.
+    pub const KEYS: &'static [(&'static str, &'static str)] = &[
+        ("Tab", "Next"),
+        ("↑ ↓ PgUp PgDn", "Scroll"),
+        ("Home End", "Oldest / Newest"),
+        ("f", "Follow"),
+        ("l", "Level"),
+        ("/", "Search"),
+        ("n N", "Older / Newer Match"),
+        ("w", "Save to File"),
+        ("e", "Export"),
+        ("c", "Listeners"),
+        ("d", "DNS"),
+        ("u", "Update SSLocal"),
+        ("v", "Releases"),
+        ("i", "Installed"),
+        ("?", "Help"),
+        ("Esc", "Exit"),
+    ];
+
+    pub fn footer(&self) -> &'static str {
+        if self.searching {
+            "Search: type to find | Done (Enter) | Clear (Esc)"
+        } else {
+            "Next (Tab) | Scroll (↑ ↓ PgUp PgDn) | Search (/) | Help (?) | Exit (Esc)"
+        }
+    }
+
+    /// Sequence numbers of the lines passing the level filter.
+    fn visible(&self, buffer: &LogBuffer) -> Vec<u64> {
+        buffer
+            .iter()
+            .filter(|(_, line)| line.level.is_none_or(|level| level <= self.level))
+            .map(|(seq, _)| seq)
+            .collect()
+    }
+
+    fn top_index(&self, visible: &[u64]) -> usize {
+        let max_top = visible.len().saturating_sub(self.height);
+        if self.follow {
+            max_top
+        } else {
+            visible.partition_point(|seq| *seq < self.top).min(max_top)
+        }
+    }
+
+    fn scroll(&mut self, delta: isize) {
+        let buffer = self.buffer.clone();
+        let buffer = buffer.read().unwrap();
+        let visible = self.visible(&buffer);
+        let max_top = visible.len().saturating_sub(self.height);
+        let top = (self.top_index(&visible) as isize + delta).clamp(0, max_top as isize) as usize;
+        if delta < 0 {
+            self.follow = false;
+        }
+        self.top = visible.get(top).copied().unwrap_or(0);
+    }
+
+    fn is_match(&self, line: &LogLine) -> bool {
+        !self.query.is_empty()
+            && line
+                .text
+                .to_ascii_lowercase()
+                .contains(&self.query.to_ascii_lowercase())
+    }
+
+    /// Moves to the next match older or newer than the current one and
+    /// scrolls it into view.
+    fn find(&mut self, older: bool) {
+        let buffer = self.buffer.clone();
+        let buffer = buffer.read().unwrap();
+        let visible = self.visible(&buffer);
+        let from = self.current_match;
+        let is_candidate = |seq: &&u64| {
+            buffer.get(**seq).is_some_and(|line| self.is_match(line))
+                && from.is_none_or(|from| if older { **seq < from } else { **seq > from })
+        };
+        let found = if older {
+            visible.iter().rev().find(is_candidate)
+        } else {
+            visible.iter().find(is_candidate)
+        };
+        let Some(seq) = found.copied() else {
+            return;
+        };
+        self.current_match = Some(seq);
+
+        let position = visible.partition_point(|s| *s < seq);
+        let top = self.top_index(&visible);
+        let top = if position < top {
+            position
+        } else if position >= top + self.height {
+            position + 1 - self.height
+        } else {
+            return;
+        };
+        self.follow = false;
+        self.top = visible[top];
+    }
+
+    fn search(&mut self) {
+        self.current_match = None;
+        self.find(true);
+    }
+
+    /// Returns whether the key was used by the pane.
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if self.searching {
+            match key.code {
+                KeyCode::Esc => {
+                    self.searching = false;
+                    self.query.clear();
+                    self.current_match = None;
+                }
+                KeyCode::Enter => self.searching = false,
+                KeyCode::Backspace => {
+                    self.query.pop();
+                    self.search();
+                }
+                KeyCode::Char(c) => {
+                    self.query.push(c);
+                    self.search();
+                }
+                _ => {}
+            }
+            return true;
+        }
+        let page = self.height.max(1) as isize;
+        match key.code {
+            KeyCode::Up => self.scroll(-1),
+            KeyCode::Down => self.scroll(1),
+            KeyCode::PageUp => self.scroll(-page),
+            KeyCode::PageDown => self.scroll(page),
+            KeyCode::Home => {
+                self.follow = false;
+                self.top = 0;
+            }
+            KeyCode::End => self.follow = true,
+            KeyCode::Char('f') => {
+                if self.follow {
+                    self.scroll(0);
+                }
+                self.follow = !self.follow;
+            }
+            KeyCode::Char('l') => self.level = self.level.next(),
+            KeyCode::Char('/') => {
+                self.searching = true;
+                self.query.clear();
+                self.current_match = None;
+            }
+            KeyCode::Char('n') => self.find(true),
+            KeyCode::Char('N') => self.find(false),
+            _ => return false,
+        }
+        true
+    }
+
+    fn line(&self, seq: u64, line: &LogLine) -> Line<'static> {
+        let style = match line.level {
+            Some(LogLevel::Error) => Style::default().red(),
+            Some(LogLevel::Warn) => Style::default().yellow(),
+            Some(LogLevel::Debug | LogLevel::Trace) => Style::default().dark_gray(),
+            _ => Style::default(),
+        };
+        if self.query.is_empty() {
+            return Line::from(Span::styled(line.text.clone(), style));
+        }
+        let highlight = if self.current_match == Some(seq) {
+            Style::default().black().on_light_green()
+        } else {
+            Style::default().black().on_yellow()
+        };
+        // ascii lowercase keeps byte offsets, so they index the original text
+        let lower = line.text.to_ascii_lowercase();
+        let query = self.query.to_ascii_lowercase();
+        let mut spans = Vec::new();
+        let mut last = 0;
+        for (i, _) in lower.match_indices(&query) {
+            spans.push(Span::styled(line.text[last..i].to_string(), style));
+            spans.push(Span::styled(
+                line.text[i..i + query.len()].to_string(),
+                highlight,
+            ));
+            last = i + query.len();
+        }
+        spans.push(Span::styled(line.text[last..].to_string(), style));
+        Line::from(spans)
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, area: Rect, focused: bool) {
+        self.height = area.height.saturating_sub(2) as usize;
+        let buffer = self.buffer.clone();
+        let buffer = buffer.read().unwrap();
+        let visible = self.visible(&buffer);
+        let top = self.top_index(&visible);
+        let lines: Vec<Line> = visible[top..]
+            .iter()
+            .take(self.height)
+            .filter_map(|seq| buffer.get(*seq).map(|line| self.line(*seq, line)))
+            .collect();
+
+        let mut title = format!("Log | Level: {}", self.level.name());
+        if self.follow {
+            title.push_str(" | Follow");
+        }
+        if buffer.has_file() {
+            title.push_str(" | Saving");
+        }
+        if self.searching {
+            title.push_str(&format!(" | /{}_", self.query));
+        } else if !self.query.is_empty() {
+            title.push_str(&format!(" | /{}", self.query));
+        }
+        let mut block = Block::bordered().title(title);
+        if focused {
+            block = block.green();
+        }
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+    }
+}
@@ -1,10 +1,12 @@
 use std::{
     io::{BufRead, BufReader},
-    process::Child,
+    net::SocketAddr,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc, RwLock,
     },
+    thread::JoinHandle,
+    time::{Duration, Instant},
 };
 
 use ratatui::{
@@ -12,20 +14,33 @@ use ratatui::{
     layout::{Constraint, Flex, Layout, Margin},
     style::{palette::tailwind::*, Color, Style, Styled, Stylize},
     text::Line,
-    widgets::{Block, HighlightSpacing, Paragraph, Row, Table, TableState, Tabs, Wrap},
+    widgets::{Block, Cell, HighlightSpacing, Paragraph, Row, Table, TableState, Tabs},
 };
 
 use super::{
+    acl::AclLayer,
+    dns_settings::DnsSettingsLayer,
+    logpane::LogPane,
     messagebox::{
-        CancelableMessageBoxLayer, CancelableMessageBoxResult, MessageBoxLayer,
+        show_warnings, CancelableMessageBoxLayer, CancelableMessageBoxResult, MessageBoxLayer,
         YesNoMessageBoxLayer,
     },
+    settings::SettingsLayer,
+    sslocal_download::SSLocalDownloadLayer,
+    sslocal_releases::SSLocalReleasesLayer,
     sslocal_update::SSLocalUpdateLayer,
+    sslocal_versions::SSLocalVersionsLayer,
+    ssserver_edit::SSServerEditLayer,
+    ssserver_export::SSServerExportLayer,
     ssserver_import::SSServerImportLayer,
+    ssserver_paste::{PasteTarget, SSServerPasteLayer},
 };
 use crate::{
-    sslocal::{SSLocal, SSLocalManager},
-    userdata::UserData,
+    acl::AclRuleSet,
+    latency::{HealthMonitor, Latency, LatencyTest},
+    logbuffer::{utc_timestamp, LogBuffer, LogFile, LogLevel},
+    sslocal::{Release, SSLocal, SSLocalChild, SSLocalManager},
+    userdata::{data_path, ServerGroup, UserData},
     Layer,
 };
 
@@ -35,6 +50,17 @@ enum State {
     Log,
 }
 
+enum Connection {
+    Stopped,
+    Running {
+        since: Instant,
+    },
+    Down {
+        status: String,
+        restart_at: Option<Instant>,
+    },
+}
+
 pub struct MainLayer {
     exit: bool,
     state: State,
@@ -44,12 +70,54 @@ pub struct MainLayer {
     table_state: TableState,
     selected_style: Style,
     sslocal: Option<SSLocal>,
-    child: Option<Child>,
+    child: Option<SSLocalChild>,
     child_stop: Arc<AtomicBool>,
-    logs: Arc<RwLock<String>>,
+    logs: Arc<RwLock<LogBuffer>>,
+    log_pane: LogPane,
+    latency_test: Option<LatencyTest>,
+    health_monitor: Option<HealthMonitor>,
+    health_failures: u32,
+    /// The last failover, shown in the header until sslocal is stopped.
+    failover_notice: Option<String>,
+    connection: Connection,
+    restart_attempts: u32,
+    /// The startup check for a newer sslocal.
+    update_check: Option<JoinHandle<anyhow::Result<Release>>>,
+    /// The tag of a newer sslocal release, shown in the header.
+    update_available: Option<String>,
+}
+
+impl Default for MainLayer {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl MainLayer {
+    /// Every key of the server table state, for the help popup.
+    const KEYS: &'static [(&'static str, &'static str)] = &[
+        ("Tab", "Next"),
+        ("↑ ↓", "Select Server"),
+        ("← →", "Select Group"),
+        ("Enter", "Connect"),
+        ("u", "Update Group"),
+        ("a", "Add Group"),
+        ("Del", "Delete Group"),
+        ("t", "Test / Cancel Latency"),
+        ("r", "Real Delay"),
+        ("f", "Failover"),
+        ("l", "ACL"),
+        ("p", "Paste"),
+        ("s", "Share"),
+        ("x", "Export Group"),
+        ("n e d", "New / Edit / Remove Server"),
+        ("?", "Help"),
+        ("Esc", "Exit"),
+    ];
+    /// A child that ran this long before exiting gets a fresh restart budget.
+    const STABLE_RUNTIME: Duration = Duration::from_secs(60);
+    const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
     pub fn new() -> Self {
         let userdata = UserData::load().unwrap_or_default();
         let row_styles = [
@@ -58,6 +126,7 @@ impl MainLayer {
         ];
         let table_state = TableState::default().with_selected(0);
         let selected_style = Style::default().fg(BLACK).bg(INDIGO.c400);
+        let logs = Arc::new(RwLock::new(LogBuffer::new()));
 
         Self {
             exit: false,
@@ -70,11 +139,44 @@ impl MainLayer {
             sslocal: None,
             child: None,
             child_stop: Arc::new(AtomicBool::new(false)),
-            logs: Arc::new(RwLock::new(String::new())),
+            logs: logs.clone(),
+            log_pane: LogPane::new(logs),
+            latency_test: None,
+            health_monitor: None,
+            health_failures: 0,
+            failover_notice: None,
+            connection: Connection::Stopped,
+            restart_attempts: 0,
+            update_check: None,
+            update_available: None,
+        }
+    }
+
+    fn poll_update_check(&mut self) {
+        if !self
+            .update_check
+            .as_ref()
+            .is_some_and(|check| check.is_finished())
+        {
+            return;
+        }
+        match self.update_check.take().unwrap().join() {
+            Ok(Ok(latest)) => {
+                if latest.is_newer_than(self.sslocal.as_ref()) {
+                    self.update_available = Some(latest.tag_name);
+                }
+            }
+            Ok(Err(err)) => self.logs.write().unwrap().push(
+                Some(LogLevel::Warn),
+                &format!("sstui: checking for a newer sslocal failed, {}", err),
+            ),
+            Err(_) => {}
         }
     }
 
-    fn sslocal_update(&mut self) -> std::io::Result<()> {
+    /// Without `interactive` the archive for this machine is installed
+    /// straight away when there is one.
+    fn sslocal_update(&mut self, interactive: bool) -> std::io::Result<()> {
         let cancelable = CancelableMessageBoxLayer::new(
             "Info",
             "get latest version...",
@@ -86,12 +188,41 @@ impl MainLayer {
         if let CancelableMessageBoxResult::Complete(result) = cancelable.result {
             match result {
                 Ok(latest) => {
+                    if !latest.is_newer_than(self.sslocal.as_ref()) {
+                        self.update_available = None;
+                        MessageBoxLayer::new(
+                            "Info",
+                            format!("sslocal {} is up to date", latest.tag_name),
+                        )
+                        .green()
+                        .on_gray()
+                        .show()?;
+                        return Ok(());
+                    }
+                    if !interactive {
+                        if let Some(asset) = latest.matching_asset() {
+                            let download =
+                                SSLocalDownloadLayer::new(latest.tag_name.clone(), asset.clone())
+                                    .show()?;
+                            if download.result.is_some() {
+                                self.sslocal = download.result;
+                                self.update_available = None;
+                            }
+                            return Ok(());
+                        }
+                    }
                     let yes_no = YesNoMessageBoxLayer::new(
                         "Info",
                         Line::from(vec![
                             "latest version:".into(),
                             format!(" {} ", latest.tag_name).white().on_red(),
-                            ", download it?".into(),
+                            match self.sslocal.as_ref().and_then(|s| s.semver.as_ref()) {
+                                Some(installed) => {
+                                    format!(", installed {}, download it?", installed)
+                                }
+                                None => ", download it?".to_string(),
+                            }
+                            .into(),
                         ]),
                     )
                     .green()
@@ -101,6 +232,7 @@ impl MainLayer {
                         let update = SSLocalUpdateLayer::new(latest).show()?;
                         if update.result.is_some() {
                             self.sslocal = update.result;
+                            self.update_available = None;
                         }
                     }
                 }
@@ -115,45 +247,521 @@ impl MainLayer {
         Ok(())
     }
 
+    fn sslocal_releases(&mut self) -> std::io::Result<()> {
+        let cancelable = CancelableMessageBoxLayer::new(
+            "Info",
+            "get releases...",
+            std::thread::spawn(SSLocalManager::get_releases),
+        )
+        .green()
+        .on_gray()
+        .show()?;
+        if let CancelableMessageBoxResult::Complete(result) = cancelable.result {
+            match result {
+                Ok(releases) => {
+                    let installed = self.sslocal.as_ref().and_then(|s| s.semver.clone());
+                    let browser = SSLocalReleasesLayer::new(releases, installed).show()?;
+                    if browser.result.is_some() {
+                        self.sslocal = browser.result;
+                        self.update_available = None;
+                    }
+                }
+                Err(err) => {
+                    MessageBoxLayer::new("Error", err.to_string())
+                        .red()
+                        .on_gray()
+                        .show()?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn sslocal_versions(&mut self) -> std::io::Result<()> {
+        let versions = SSLocalVersionsLayer::new().show()?;
+        if versions.result.is_some() {
+            self.sslocal = versions.result;
+            self.update_available = None;
+            self.update_check = Some(std::thread::spawn(SSLocalManager::get_latest));
+        }
+        Ok(())
+    }
+
+    /// Only manual groups can be edited by hand, subscriptions would be
+    /// overwritten on the next refresh.
+    fn check_manual_group(&self) -> std::io::Result<bool> {
+        match self.userdata.server_groups.get(self.show_group_index) {
+            Some(group) if group.is_manual() => Ok(true),
+            Some(group) => {
+                MessageBoxLayer::new(
+                    "Info",
+                    format!(
+                        "'{}' is a subscription, only manual groups can be edited",
+                        group.name
+                    ),
+                )
+                .green()
+                .on_gray()
+                .show()?;
+                Ok(false)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn add_server(&mut self) -> std::io::Result<()> {
+        if self.check_manual_group()? {
+            let edit = SSServerEditLayer::new(None).show()?;
+            if let Some(server) = edit.result {
+                let group = &mut self.userdata.server_groups[self.show_group_index];
+                group.ss_servers.push(server);
+                self.table_state.select(Some(group.ss_servers.len() - 1));
+                self.userdata.save()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn edit_server(&mut self) -> std::io::Result<()> {
+        if self.check_manual_group()? {
+            let group = &self.userdata.server_groups[self.show_group_index];
+            if let Some(server) = self
+                .table_state
+                .selected()
+                .and_then(|i| group.ss_servers.get(i))
+            {
+                let edit = SSServerEditLayer::new(Some(server)).show()?;
+                if let Some(server) = edit.result {
+                    let i = self.table_state.selected().unwrap();
+                    self.userdata.server_groups[self.show_group_index].ss_servers[i] = server;
+                    self.userdata.save()?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn remove_server(&mut self) -> std::io::Result<()> {
+        if self.check_manual_group()? {
+            let group = &self.userdata.server_groups[self.show_group_index];
+            if let Some((i, server)) = self
+                .table_state
+                .selected()
+                .and_then(|i| group.ss_servers.get(i).map(|server| (i, server)))
+            {
+                let yes_no = YesNoMessageBoxLayer::new(
+                    "Info",
+                    format!("delete server '{}' ?", server.remarks),
+                )
+                .red()
+                .on_gray()
+                .show()?;
+                if yes_no.result.is_yes() {
+                    if self.userdata.selected_server == Some((self.show_group_index, i)) {
+                        self.stop_sslocal();
+                    }
+                    self.cancel_latency_test();
+                    self.userdata.remove_server(self.show_group_index, i);
+                    self.userdata.save()?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn paste_import(&mut self, text: &str) -> std::io::Result<()> {
+        let groups = self
+            .userdata
+            .server_groups
+            .iter()
+            .enumerate()
+            .filter(|(_, group)| group.is_manual())
+            .map(|(i, group)| (i, group.name.clone()))
+            .collect();
+        let paste = SSServerPasteLayer::new(groups, text).show()?;
+        if let Some((target, servers)) = paste.result {
+            match target {
+                PasteTarget::NewGroup(name) => {
+                    let mut group = ServerGroup::manual(name);
+                    group.ss_servers = servers;
+                    self.userdata.server_groups.push(group);
+                    self.show_group_index = self.userdata.server_groups.len() - 1;
+                    self.table_state.select(Some(0));
+                }
+                PasteTarget::Group(i) => {
+                    self.userdata.server_groups[i].ss_servers.extend(servers);
+                    self.show_group_index = i;
+                }
+            }
+            self.userdata.save()?;
+        }
+        Ok(())
+    }
+
+    /// `real` probes `UserData::probe_url` through a temporary sslocal per
+    /// server instead of only connecting to it.
+    fn start_latency_test(&mut self, real: bool) -> std::io::Result<()> {
+        if real && self.sslocal.is_none() {
+            MessageBoxLayer::new("Error", "sslocal not found")
+                .red()
+                .on_gray()
+                .show()?;
+            return Ok(());
+        }
+        if let Some(group) = self.userdata.server_groups.get_mut(self.show_group_index) {
+            for server in group.ss_servers.iter_mut() {
+                server.latency = Some(Latency::Testing);
+            }
+            self.latency_test = Some(match &self.sslocal {
+                Some(sslocal) if real => LatencyTest::real(
+                    self.show_group_index,
+                    &group.ss_servers,
+                    sslocal.clone(),
+                    self.userdata.probe_url.clone(),
+                ),
+                _ => LatencyTest::tcp(self.show_group_index, &group.ss_servers),
+            });
+        }
+        Ok(())
+    }
+
+    /// Stops a running latency test, clearing the servers it did not reach.
+    fn cancel_latency_test(&mut self) {
+        if let Some(test) = self.latency_test.take() {
+            test.cancel();
+            if let Some(group) = self.userdata.server_groups.get_mut(test.group_index) {
+                for server in group.ss_servers.iter_mut() {
+                    if let Some(Latency::Testing) = server.latency {
+                        server.latency = None;
+                    }
+                }
+            }
+        }
+    }
+
+    fn poll_latency_test(&mut self) {
+        if let Some(test) = &mut self.latency_test {
+            if let Some(group) = self.userdata.server_groups.get_mut(test.group_index) {
+                for (i, latency) in test.try_recv() {
+                    if let Some(server) = group.ss_servers.get_mut(i) {
+                        server.latency = Some(latency);
+                    }
+                }
+            }
+            if test.is_finished() {
+                self.latency_test = None;
+            }
+        }
+    }
+
+    fn start_health_monitor(&mut self) {
+        self.health_failures = 0;
+        self.health_monitor = if self.userdata.health_check.enabled && self.child.is_some() {
+            Some(HealthMonitor::start(
+                SocketAddr::from(([127, 0, 0, 1], self.userdata.local_port)),
+                self.userdata.probe_url.clone(),
+                Duration::from_secs(self.userdata.health_check.interval_secs.max(1)),
+            ))
+        } else {
+            None
+        };
+    }
+
+    fn poll_health_monitor(&mut self) {
+        let Some(health_monitor) = &self.health_monitor else {
+            return;
+        };
+        for latency in health_monitor.try_recv() {
+            if latency.as_duration().is_some() {
+                self.health_failures = 0;
+            } else {
+                self.health_failures += 1;
+            }
+        }
+        if self.health_failures >= self.userdata.health_check.max_failures.max(1) {
+            self.failover();
+        }
+    }
+
+    /// Switches to the next best server of the group after the current one
+    /// failed too many health checks.
+    fn failover(&mut self) {
+        let failures = self.health_failures;
+        self.health_failures = 0;
+        let Some((group_index, server_index)) = self.userdata.selected_server else {
+            return;
+        };
+        let group = &self.userdata.server_groups[group_index];
+        let Some(next_index) = group.next_best_server(server_index) else {
+            return;
+        };
+        let from = group.ss_servers[server_index].remarks.clone();
+        let to = group.ss_servers[next_index].remarks.clone();
+        self.stop_sslocal();
+        let notice = match self.run_sslocal(group_index, next_index) {
+            Ok(_) => format!(
+                "failover: '{}' -> '{}' after {} failed health checks",
+                from, to, failures
+            ),
+            Err(err) => format!("failover: '{}' -> '{}' failed: {}", from, to, err),
+        };
+        self.logs
+            .write()
+            .unwrap()
+            .push(Some(LogLevel::Warn), &notice);
+        self.failover_notice = Some(notice);
+    }
+
+    fn help(&self) -> std::io::Result<()> {
+        let keys = match self.state {
+            State::Tab => Self::KEYS,
+            State::Log => LogPane::KEYS,
+        };
+        let width = keys
+            .iter()
+            .map(|(key, _)| key.chars().count())
+            .max()
+            .unwrap_or(0);
+        let help = keys
+            .iter()
+            .map(|(key, action)| format!("{:>width$}  {:<26}", key, action, width = width))
+            .collect::<Vec<_>>()
+            .join("\n");
+        MessageBoxLayer::new("Help", help)
+            .green()
+            .on_gray()
+            .show()?;
+        Ok(())
+    }
+
+    /// Starts or stops copying the log into rotating files.
+    fn open_log_files(&mut self) {
+        let config = &self.userdata.log_files;
+        let file = if config.enabled {
+            match data_path("logs")
+                .and_then(|dir| LogFile::open(dir, config.max_size_kb * 1024, config.max_files))
+            {
+                Ok(file) => Some(file),
+                Err(err) => {
+                    self.logs.write().unwrap().push(
+                        Some(LogLevel::Error),
+                        &format!("cannot open log file: {}", err),
+                    );
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        self.logs.write().unwrap().set_file(file);
+    }
+
+    fn settings(&mut self) -> std::io::Result<()> {
+        let settings = SettingsLayer::new(self.userdata.all_listeners()).show()?;
+        if let Some(listeners) = settings.result {
+            self.userdata.set_listeners(listeners);
+            self.userdata.save()?;
+            self.restart_sslocal()?;
+        }
+        Ok(())
+    }
+
+    fn acl(&mut self) -> std::io::Result<()> {
+        let group = self
+            .userdata
+            .server_groups
+            .get(self.show_group_index)
+            .map(|group| (group.name.clone(), group.acl.clone()));
+        let proxy = self
+            .child
+            .is_some()
+            .then(|| format!("socks5://127.0.0.1:{}", self.userdata.local_port));
+        let acl = AclLayer::new(self.userdata.acl.clone(), group, proxy).show()?;
+        if !acl.changed {
+            return Ok(());
+        }
+        self.userdata.acl = acl.global.clone();
+        if let Some(group) = self.userdata.server_groups.get_mut(self.show_group_index) {
+            group.acl = acl.group.clone();
+        }
+        // other groups may point at a deleted rule set, unknown if listing failed
+        if acl.loaded {
+            let names = acl.names();
+            let exists = |name: &Option<String>| name.as_ref().is_none_or(|n| names.contains(n));
+            if !exists(&self.userdata.acl) {
+                self.userdata.acl = None;
+            }
+            for group in &mut self.userdata.server_groups {
+                if !exists(&group.acl) {
+                    group.acl = None;
+                }
+            }
+        }
+        self.userdata.save()?;
+        self.restart_sslocal()
+    }
+
+    fn dns_settings(&mut self) -> std::io::Result<()> {
+        let settings = DnsSettingsLayer::new(&self.userdata.dns).show()?;
+        if let Some(dns) = settings.result {
+            self.userdata.dns = dns;
+            self.userdata.save()?;
+            self.restart_sslocal()?;
+        }
+        Ok(())
+    }
+
+    /// Applies changed settings to a running sslocal.
+    fn restart_sslocal(&mut self) -> std::io::Result<()> {
+        if self.child.is_none() {
+            return Ok(());
+        }
+        let Some((group_index, server_index)) = self.userdata.selected_server else {
+            return Ok(());
+        };
+        self.stop_sslocal();
+        if let Err(err) = self.run_sslocal(group_index, server_index) {
+            MessageBoxLayer::new("Error", err.to_string())
+                .red()
+                .on_gray()
+                .show()?;
+        }
+        Ok(())
+    }
+
+    fn export_logs(&mut self) -> std::io::Result<()> {
+        let result = data_path("logs").and_then(|dir| {
+            std::fs::create_dir_all(&dir)?;
+            let path = dir.join(format!("export-{}.log", utc_timestamp()));
+            self.logs.read().unwrap().export(&path)?;
+            Ok(path)
+        });
+        match result {
+            Ok(path) => {
+                MessageBoxLayer::new("Info", format!("exported to '{}'", path.display()))
+                    .green()
+                    .on_gray()
+                    .show()?;
+            }
+            Err(err) => {
+                MessageBoxLayer::new("Error", err.to_string())
+                    .red()
+                    .on_gray()
+                    .show()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Also drops the failover notice, which only describes the connection
+    /// being stopped.
     fn stop_sslocal(&mut self) {
+        self.health_monitor = None;
+        self.failover_notice = None;
+        self.connection = Connection::Stopped;
+        self.restart_attempts = 0;
         self.child_stop.store(true, Ordering::Relaxed);
         if let Some(mut child) = self.child.take() {
-            child.kill().unwrap();
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+
+    /// Notices a crashed sslocal and restarts it with exponential backoff.
+    fn supervise(&mut self) {
+        if let Some(child) = &mut self.child {
+            if let Ok(Some(status)) = child.try_wait() {
+                self.child = None;
+                self.health_monitor = None;
+                if let Connection::Running { since } = self.connection {
+                    if since.elapsed() >= Self::STABLE_RUNTIME {
+                        self.restart_attempts = 0;
+                    }
+                }
+                self.logs.write().unwrap().push(
+                    Some(LogLevel::Error),
+                    &format!("sslocal exited ({})", status),
+                );
+                self.set_down(status.to_string());
+            }
+        }
+
+        if let Connection::Down {
+            restart_at: Some(restart_at),
+            ..
+        } = self.connection
+        {
+            if Instant::now() >= restart_at {
+                self.restart_attempts += 1;
+                if let Some((group_index, server_index)) = self.userdata.selected_server {
+                    if let Err(err) = self.run_sslocal(group_index, server_index) {
+                        self.logs.write().unwrap().push(
+                            Some(LogLevel::Error),
+                            &format!("failed to restart sslocal: {}", err),
+                        );
+                        self.set_down(err.to_string());
+                    }
+                } else {
+                    self.connection = Connection::Stopped;
+                }
+            }
         }
     }
 
+    fn set_down(&mut self, status: String) {
+        let restart_at = (self.restart_attempts < self.userdata.max_restarts).then(|| {
+            let backoff = Duration::from_secs(1 << self.restart_attempts.min(6));
+            Instant::now() + backoff.min(Self::MAX_BACKOFF)
+        });
+        self.connection = Connection::Down { status, restart_at };
+    }
+
     fn run_sslocal(&mut self, group_index: usize, server_index: usize) -> std::io::Result<()> {
         let server = &self.userdata.server_groups[group_index].ss_servers[server_index];
         if let Some(sslocal) = &self.sslocal {
-            match sslocal.run(server, self.userdata.local_port, self.userdata.lan_support) {
-                Ok(mut child) => {
-                    self.userdata.selected_server = Some((group_index, server_index));
-                    if child.stdout.is_some() {
-                        let mut reader = BufReader::new(child.stdout.take().unwrap());
-                        self.child_stop.store(false, Ordering::Relaxed);
-                        let child_stop = self.child_stop.clone();
-                        let logs = self.logs.clone();
-                        std::thread::spawn(move || loop {
-                            if child_stop.load(Ordering::Relaxed) {
-                                break;
-                            }
-                            let mut buf = String::new();
-                            if let Ok(n) = reader.read_line(&mut buf) {
-                                if n > 0 {
-                                    *logs.write().unwrap() = buf;
-                                }
-                            }
-                        });
+            let acl = match self.userdata.acl_for(group_index) {
+                Some(name) => Some(AclRuleSet::load(name).and_then(|acl| acl.generate())?),
+                None => None,
+            };
+            let mut child = sslocal.run(
+                server,
+                &self.userdata.all_listeners(),
+                &self.userdata.dns,
+                acl.as_deref(),
+            )?;
+            self.userdata.selected_server = Some((group_index, server_index));
+            if child.stdout.is_some() {
+                let mut reader = BufReader::new(child.stdout.take().unwrap());
+                self.child_stop.store(false, Ordering::Relaxed);
+                let child_stop = self.child_stop.clone();
+                let logs = self.logs.clone();
+                std::thread::spawn(move || loop {
+                    if child_stop.load(Ordering::Relaxed) {
+                        break;
                     }
-                    self.child = Some(child);
-                }
-                Err(err) => {
-                    MessageBoxLayer::new("Error", err.to_string())
-                        .red()
-                        .on_gray()
-                        .show()?;
-                }
+                    let mut buf = String::new();
+                    match reader.read_line(&mut buf) {
+                        Ok(0) | Err(_) => break,
+                        Ok(_) => logs.write().unwrap().push_output(&buf),
+                    }
+                });
+            }
+            if let Some(stderr) = child.stderr.take() {
+                let mut reader = BufReader::new(stderr);
+                let logs = self.logs.clone();
+                std::thread::spawn(move || loop {
+                    let mut buf = String::new();
+                    match reader.read_line(&mut buf) {
+                        Ok(0) | Err(_) => break,
+                        Ok(_) => logs.write().unwrap().push_output(&buf),
+                    }
+                });
             }
+            self.child = Some(child);
+            self.connection = Connection::Running {
+                since: Instant::now(),
+            };
+            self.start_health_monitor();
         }
         Ok(())
     }
@@ -161,6 +769,8 @@ impl MainLayer {
 
 impl Layer for MainLayer {
     fn before_show(&mut self) -> std::io::Result<()> {
+        self.open_log_files();
+
         // sslocal
         self.sslocal = SSLocalManager::find_sslocal()?;
         if self.sslocal.is_none() {
@@ -169,8 +779,10 @@ impl Layer for MainLayer {
                 .on_gray()
                 .show()?;
             if yes_no.result.is_yes() {
-                self.sslocal_update()?;
+                self.sslocal_update(false)?;
             }
+        } else {
+            self.update_check = Some(std::thread::spawn(SSLocalManager::get_latest));
         }
 
         // update servers
@@ -188,18 +800,76 @@ impl Layer for MainLayer {
             ])
             .flex(Flex::Legacy)
             .areas(frame.area());
-        let header = if let Some(sslocal) = &self.sslocal {
-            Paragraph::new(format!("Version: {}", sslocal.version))
+        let mut header = if let Some(sslocal) = &self.sslocal {
+            Line::from(format!("Version: {}", sslocal.version.trim()))
         } else {
-            Paragraph::new("Version: None")
+            Line::from("Version: None")
         };
+        if self.userdata.dns.enabled {
+            let dns = format!(
+                " | DNS: {}:{}",
+                self.userdata.dns.address(),
+                self.userdata.dns.port
+            );
+            match self.connection {
+                Connection::Running { .. } => header.push_span(dns.green()),
+                _ => header.push_span(dns.dark_gray()),
+            }
+        }
+        match &self.connection {
+            Connection::Stopped => {}
+            Connection::Running { .. } => header.push_span(" | Running".green()),
+            Connection::Down { status, restart_at } => {
+                let down = match restart_at {
+                    Some(restart_at) => format!(
+                        " | Down ({}), restart {}/{} in {}s",
+                        status,
+                        self.restart_attempts + 1,
+                        self.userdata.max_restarts,
+                        restart_at
+                            .saturating_duration_since(Instant::now())
+                            .as_secs_f32()
+                            .ceil()
+                    ),
+                    None => format!(
+                        " | Down ({}), gave up after {} restarts",
+                        status, self.restart_attempts
+                    ),
+                };
+                header.push_span(down.red());
+            }
+        }
+        if self.userdata.health_check.enabled {
+            header.push_span(" | Failover: on".green());
+        }
+        if let Some(notice) = &self.failover_notice {
+            header.push_span(" | ");
+            header.push_span(notice.as_str().yellow());
+        }
+        if let Some(tag) = &self.update_available {
+            header.push_span(" | ");
+            header.push_span(format!(" {} available ", tag).white().on_red());
+        }
+        let header = Paragraph::new(header);
         frame.render_widget(header, header_layout);
 
-        let tabs: Vec<&str> = self
+        let tabs: Vec<String> = self
             .userdata
             .server_groups
             .iter()
-            .map(|i| i.name.as_str())
+            .map(|i| match (i.bytes_used, i.bytes_remaining) {
+                (Some(used), Some(remaining)) => format!(
+                    "{} ({} / {})",
+                    i.name,
+                    format_bytes(used),
+                    format_bytes(used.saturating_add(remaining))
+                ),
+                (Some(used), None) => format!("{} ({} used)", i.name, format_bytes(used)),
+                (None, Some(remaining)) => {
+                    format!("{} ({} left)", i.name, format_bytes(remaining))
+                }
+                (None, None) => i.name.clone(),
+            })
             .collect();
         if !tabs.is_empty() {
             let mut current_group = false;
@@ -216,16 +886,17 @@ impl Layer for MainLayer {
                 .iter()
                 .map(|i| i.server_port.to_string())
                 .collect();
-            let (name_len, server_len, port_len, method_len) = selected_server_group
+            let (name_len, server_len, port_len, method_len, plugin_len) = selected_server_group
                 .ss_servers
                 .iter()
                 .enumerate()
-                .fold((0, 0, 0, 0), |(n, s, p, m), (i, server)| {
+                .fold((0, 0, 0, 0, 0), |(n, s, p, m, pl), (i, server)| {
                     (
                         n.max(server.remarks.len()),
                         s.max(server.server.len()),
                         p.max(server_port_str_vec[i].len()),
                         m.max(server.method.len()),
+                        pl.max(server.plugin.as_ref().map_or(0, |plugin| plugin.len())),
                     )
                 });
             let tabs = Tabs::new(tabs)
@@ -233,29 +904,29 @@ impl Layer for MainLayer {
                 .highlight_style(Style::default().fg(Color::Black).bg(Color::Yellow));
             frame.render_widget(tabs, tabs_layout);
 
-            let header = Row::new(["Name", "Server", "Port", "Method"]).white();
+            let header =
+                Row::new(["Name", "Server", "Port", "Method", "Plugin", "Latency"]).white();
             let rows = selected_server_group
                 .ss_servers
                 .iter()
                 .enumerate()
                 .map(|(i, server)| {
+                    let row = Row::new([
+                        Cell::from(server.remarks.as_str()),
+                        Cell::from(server.server.as_str()),
+                        Cell::from(server_port_str_vec[i].as_str()),
+                        Cell::from(server.method.as_str()),
+                        Cell::from(server.plugin.as_deref().unwrap_or_default()),
+                        latency_cell(server.latency.as_ref()),
+                    ]);
                     if current_group && i == used_index {
-                        Row::new([
-                            server.remarks.as_str(),
-                            server.server.as_str(),
-                            server_port_str_vec[i].as_str(),
-                            server.method.as_str(),
-                        ])
-                        .white()
-                        .on_green()
+                        match self.connection {
+                            Connection::Stopped => row.green().on_black(),
+                            Connection::Running { .. } => row.white().on_green(),
+                            Connection::Down { .. } => row.white().on_red(),
+                        }
                     } else {
-                        Row::new([
-                            server.remarks.as_str(),
-                            server.server.as_str(),
-                            server_port_str_vec[i].as_str(),
-                            server.method.as_str(),
-                        ])
-                        .set_style(self.row_styles[i % 2])
+                        row.set_style(self.row_styles[i % 2])
                     }
                 });
             let table = Table::new(
@@ -265,6 +936,8 @@ impl Layer for MainLayer {
                     Constraint::Length(server_len as u16 + 1),
                     Constraint::Length(port_len as u16 + 1),
                     Constraint::Length(method_len as u16 + 1),
+                    Constraint::Length(plugin_len as u16 + 1),
+                    Constraint::Length(12),
                 ],
             )
             .header(header)
@@ -304,109 +977,207 @@ impl Layer for MainLayer {
             frame.render_widget(main, center);
         }
 
-        let log = self.logs.read().unwrap();
-        let mut log = Paragraph::new(log.as_str())
-            .block(Block::bordered().title("Log"))
-            .wrap(Wrap { trim: true });
-        if self.state == State::Log {
-            log = log.green();
-        }
-        frame.render_widget(log, log_layout);
+        self.log_pane
+            .render(frame, log_layout, self.state == State::Log);
 
         let op: &str = if let State::Tab = self.state {
-            "Next (Tab) | ↑ | ↓ | ← | → | Update (u) | Add (a) | Del (Del) | Select (Enter) | Exit (Esc)"
+            "Next (Tab) | Select (Enter) | Update (u) | Add (a) | Help (?) | Exit (Esc)"
         } else {
-            "Next (Tab) | ↑ | ↓ | Select (Enter) | Configure (c) | Update SSLocal (u) | Exit (Esc)"
+            self.log_pane.footer()
         };
         let footer = Paragraph::new(op).centered();
         frame.render_widget(footer, footer_layout);
     }
 
     fn update(&mut self, event: Option<Event>) -> std::io::Result<()> {
-        if let Some(event) = event {
-            if let Event::Key(key_event) = event {
-                if key_event.kind == KeyEventKind::Press {
-                    match key_event.code {
-                        KeyCode::Esc => {
-                            self.exit = YesNoMessageBoxLayer::new("Info", "exit?")
-                                .green()
-                                .on_gray()
-                                .show()?
-                                .result
-                                .is_yes();
-                            if self.exit {
-                                self.userdata.save()?;
-                                self.stop_sslocal();
+        self.poll_latency_test();
+        self.poll_update_check();
+        self.supervise();
+        self.poll_health_monitor();
+
+        if let Some(Event::Paste(text)) = &event {
+            if let State::Tab = self.state {
+                self.paste_import(text)?;
+            }
+        }
+        if let Some(Event::Key(key_event)) = event {
+            if key_event.kind == KeyEventKind::Press
+                && !(self.state == State::Log && self.log_pane.handle_key(key_event))
+            {
+                match key_event.code {
+                    KeyCode::Esc => {
+                        self.exit = YesNoMessageBoxLayer::new("Info", "exit?")
+                            .green()
+                            .on_gray()
+                            .show()?
+                            .result
+                            .is_yes();
+                        if self.exit {
+                            self.userdata.save()?;
+                            self.stop_sslocal();
+                        }
+                    }
+                    KeyCode::Tab => {
+                        self.state = match self.state {
+                            State::Tab => State::Log,
+                            State::Log => State::Tab,
+                        };
+                    }
+                    KeyCode::Left => {
+                        if let State::Tab = self.state {
+                            let before = self.show_group_index;
+                            self.show_group_index = self.show_group_index.saturating_sub(1);
+                            if before != self.show_group_index {
+                                self.table_state.select(Some(0));
                             }
                         }
-                        KeyCode::Tab => {
-                            self.state = match self.state {
-                                State::Tab => State::Log,
-                                State::Log => State::Tab,
-                            };
+                    }
+                    KeyCode::Right => {
+                        if let State::Tab = self.state {
+                            let before = self.show_group_index;
+                            self.show_group_index = (self.show_group_index + 1)
+                                .min(self.userdata.server_groups.len() - 1);
+                            if before != self.show_group_index {
+                                self.table_state.select(Some(0));
+                            }
                         }
-                        KeyCode::Left => {
-                            if let State::Tab = self.state {
-                                let before = self.show_group_index;
-                                self.show_group_index = self.show_group_index.saturating_sub(1);
-                                if before != self.show_group_index {
-                                    self.table_state.select(Some(0));
-                                }
+                    }
+                    KeyCode::Up => {
+                        self.table_state.select_previous();
+                    }
+                    KeyCode::Down => {
+                        self.table_state.select_next();
+                    }
+                    KeyCode::Char('a') => {
+                        if let State::Tab = self.state {
+                            let import = SSServerImportLayer::new().show()?;
+                            if let Some(group) = import.result {
+                                self.userdata.server_groups.push(group);
+                                self.userdata.save()?;
                             }
                         }
-                        KeyCode::Right => {
-                            if let State::Tab = self.state {
-                                let before = self.show_group_index;
-                                self.show_group_index = (self.show_group_index + 1)
-                                    .min(self.userdata.server_groups.len() - 1);
-                                if before != self.show_group_index {
-                                    self.table_state.select(Some(0));
-                                }
+                    }
+                    KeyCode::Char(c @ ('t' | 'r')) => {
+                        if let State::Tab = self.state {
+                            if self.latency_test.is_some() {
+                                self.cancel_latency_test();
+                            } else {
+                                self.start_latency_test(c == 'r')?;
                             }
                         }
-                        KeyCode::Up => {
-                            self.table_state.select_previous();
+                    }
+                    KeyCode::Char('f') => {
+                        if let State::Tab = self.state {
+                            self.userdata.health_check.enabled =
+                                !self.userdata.health_check.enabled;
+                            self.start_health_monitor();
+                            self.userdata.save()?;
                         }
-                        KeyCode::Down => {
-                            self.table_state.select_next();
+                    }
+                    KeyCode::Char('s') => {
+                        if let State::Tab = self.state {
+                            if let Some(server) = self
+                                .userdata
+                                .server_groups
+                                .get(self.show_group_index)
+                                .zip(self.table_state.selected())
+                                .and_then(|(group, i)| group.ss_servers.get(i))
+                            {
+                                SSServerExportLayer::new(server).show()?;
+                            }
                         }
-                        KeyCode::Char('a') => {
-                            if let State::Tab = self.state {
-                                let import = SSServerImportLayer::new().show()?;
-                                if let Some(group) = import.result {
-                                    self.userdata.server_groups.push(group);
-                                    self.userdata.save()?;
+                    }
+                    KeyCode::Char('x') => {
+                        if let State::Tab = self.state {
+                            if let Some(group) =
+                                self.userdata.server_groups.get(self.show_group_index)
+                            {
+                                match group.export() {
+                                    Ok(path) => {
+                                        MessageBoxLayer::new(
+                                            "Info",
+                                            format!("exported to '{}'", path.display()),
+                                        )
+                                        .green()
+                                        .on_gray()
+                                        .show()?;
+                                    }
+                                    Err(err) => {
+                                        MessageBoxLayer::new("Error", err.to_string())
+                                            .red()
+                                            .on_gray()
+                                            .show()?;
+                                    }
                                 }
                             }
                         }
-                        KeyCode::Delete => {
-                            if let State::Tab = self.state {
-                                if self.show_group_index < self.userdata.server_groups.len() {
-                                    let yes_no = YesNoMessageBoxLayer::new(
-                                        "Info",
-                                        format!(
-                                            "delete group '{}' ?",
-                                            self.userdata.server_groups[self.show_group_index].name
-                                        ),
-                                    )
-                                    .red()
-                                    .on_gray()
-                                    .show()?;
-                                    if yes_no.result.is_yes() {
-                                        self.userdata.server_groups.remove(self.show_group_index);
-                                        self.show_group_index =
-                                            self.show_group_index.saturating_sub(1);
-                                        self.userdata.save()?;
+                    }
+                    KeyCode::Char('l') => {
+                        if let State::Tab = self.state {
+                            self.acl()?;
+                        }
+                    }
+                    KeyCode::Char('p') => {
+                        if let State::Tab = self.state {
+                            self.paste_import("")?;
+                        }
+                    }
+                    KeyCode::Char('n') => {
+                        if let State::Tab = self.state {
+                            self.add_server()?;
+                        }
+                    }
+                    KeyCode::Char('e') => match self.state {
+                        State::Tab => self.edit_server()?,
+                        State::Log => self.export_logs()?,
+                    },
+                    KeyCode::Char('w') => {
+                        if let State::Log = self.state {
+                            self.userdata.log_files.enabled = !self.userdata.log_files.enabled;
+                            self.open_log_files();
+                            self.userdata.save()?;
+                        }
+                    }
+                    KeyCode::Char('d') => match self.state {
+                        State::Tab => self.remove_server()?,
+                        State::Log => self.dns_settings()?,
+                    },
+                    KeyCode::Delete => {
+                        if let State::Tab = self.state {
+                            if self.show_group_index < self.userdata.server_groups.len() {
+                                let yes_no = YesNoMessageBoxLayer::new(
+                                    "Info",
+                                    format!(
+                                        "delete group '{}' ?",
+                                        self.userdata.server_groups[self.show_group_index].name
+                                    ),
+                                )
+                                .red()
+                                .on_gray()
+                                .show()?;
+                                if yes_no.result.is_yes() {
+                                    if self
+                                        .userdata
+                                        .selected_server
+                                        .is_some_and(|(i, _)| i == self.show_group_index)
+                                    {
+                                        self.stop_sslocal();
                                     }
+                                    self.cancel_latency_test();
+                                    self.userdata.remove_group(self.show_group_index);
+                                    self.show_group_index = self.show_group_index.saturating_sub(1);
+                                    self.userdata.save()?;
                                 }
                             }
                         }
-                        KeyCode::Char('u') => match self.state {
-                            State::Tab => {
-                                if self.show_group_index < self.userdata.server_groups.len() {
-                                    if let Err(err) =
-                                        self.userdata.server_groups[self.show_group_index].update()
-                                    {
+                    }
+                    KeyCode::Char('u') => match self.state {
+                        State::Tab => {
+                            if self.show_group_index < self.userdata.server_groups.len() {
+                                self.cancel_latency_test();
+                                match self.userdata.update_group(self.show_group_index) {
+                                    Ok(warnings) => show_warnings(&warnings)?,
+                                    Err(err) => {
                                         MessageBoxLayer::new("Error", err.to_string())
                                             .red()
                                             .on_gray()
@@ -414,28 +1185,42 @@ impl Layer for MainLayer {
                                     }
                                 }
                             }
-                            State::Log => self.sslocal_update()?,
-                        },
-                        KeyCode::Char('c') => {}
-                        KeyCode::Enter => match self.state {
-                            State::Tab => {
-                                if self.show_group_index < self.userdata.server_groups.len() {
-                                    if let Some(i) = self.table_state.selected() {
-                                        self.stop_sslocal();
-                                        if let Err(err) = self.run_sslocal(self.show_group_index, i)
-                                        {
-                                            MessageBoxLayer::new("Error", err.to_string())
-                                                .red()
-                                                .on_gray()
-                                                .show()?;
-                                        }
+                        }
+                        State::Log => self.sslocal_update(true)?,
+                    },
+                    KeyCode::Char('c') => {
+                        if let State::Log = self.state {
+                            self.settings()?;
+                        }
+                    }
+                    KeyCode::Char('v') => {
+                        if let State::Log = self.state {
+                            self.sslocal_releases()?;
+                        }
+                    }
+                    KeyCode::Char('i') => {
+                        if let State::Log = self.state {
+                            self.sslocal_versions()?;
+                        }
+                    }
+                    KeyCode::Char('?') => self.help()?,
+                    KeyCode::Enter => match self.state {
+                        State::Tab => {
+                            if self.show_group_index < self.userdata.server_groups.len() {
+                                if let Some(i) = self.table_state.selected() {
+                                    self.stop_sslocal();
+                                    if let Err(err) = self.run_sslocal(self.show_group_index, i) {
+                                        MessageBoxLayer::new("Error", err.to_string())
+                                            .red()
+                                            .on_gray()
+                                            .show()?;
                                     }
                                 }
                             }
-                            State::Log => {}
-                        },
-                        _ => {}
-                    }
+                        }
+                        State::Log => {}
+                    },
+                    _ => {}
                 }
             }
         }
@@ -450,3 +1235,32 @@ impl Layer for MainLayer {
         self.exit = true;
     }
 }
+
+fn latency_cell(latency: Option<&Latency>) -> Cell<'static> {
+    match latency {
+        None => Cell::default(),
+        Some(Latency::Testing) => Cell::from("..."),
+        Some(Latency::Ok(duration)) => {
+            let ms = duration.as_millis();
+            let color = match ms {
+                0..200 => Color::LightGreen,
+                200..500 => Color::LightYellow,
+                _ => Color::LightRed,
+            };
+            Cell::from(format!("{} ms", ms)).fg(color)
+        }
+        Some(Latency::Timeout) => Cell::from("timeout").fg(Color::Red),
+        Some(Latency::Rejected) => Cell::from("rejected").fg(Color::Magenta),
+        Some(Latency::Failed(reason)) => Cell::from(reason.clone()).fg(Color::Red),
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const GB: f64 = 1024.0 * 1024.0 * 1024.0;
+    const MB: f64 = 1024.0 * 1024.0;
+    if bytes as f64 >= GB {
+        format!("{:.2} GB", bytes as f64 / GB)
+    } else {
+        format!("{:.2} MB", bytes as f64 / MB)
+    }
+}
@@ -55,14 +55,12 @@ impl<'a> Layer for MessageBoxLayer<'a> {
     }
 
     fn update(&mut self, event: Option<Event>) -> std::io::Result<()> {
-        if let Some(event) = event {
-            if let Event::Key(key_event) = event {
-                if key_event.kind == KeyEventKind::Press {
-                    match key_event.code {
-                        KeyCode::Enter => self.exit = true,
-                        KeyCode::Esc => self.exit = true,
-                        _ => {}
-                    }
+        if let Some(Event::Key(key_event)) = event {
+            if key_event.kind == KeyEventKind::Press {
+                match key_event.code {
+                    KeyCode::Enter => self.exit = true,
+                    KeyCode::Esc => self.exit = true,
+                    _ => {}
                 }
             }
         }
@@ -89,6 +87,7 @@ impl YesNoMessageBoxResult {
         *self == YesNoMessageBoxResult::Yes
     }
 
+    #[allow(dead_code)]
     pub fn is_no(&self) -> bool {
         *self == YesNoMessageBoxResult::No
     }
@@ -158,26 +157,24 @@ impl<'a> Layer for YesNoMessageBoxLayer<'a> {
     }
 
     fn update(&mut self, event: Option<Event>) -> std::io::Result<()> {
-        if let Some(event) = event {
-            if let Event::Key(key_event) = event {
-                if key_event.kind == KeyEventKind::Press {
-                    match key_event.code {
-                        KeyCode::Left => self.result = YesNoMessageBoxResult::Yes,
-                        KeyCode::Right => self.result = YesNoMessageBoxResult::No,
-                        KeyCode::Tab => {
-                            self.result = if self.result == YesNoMessageBoxResult::Yes {
-                                YesNoMessageBoxResult::No
-                            } else {
-                                YesNoMessageBoxResult::Yes
-                            }
+        if let Some(Event::Key(key_event)) = event {
+            if key_event.kind == KeyEventKind::Press {
+                match key_event.code {
+                    KeyCode::Left => self.result = YesNoMessageBoxResult::Yes,
+                    KeyCode::Right => self.result = YesNoMessageBoxResult::No,
+                    KeyCode::Tab => {
+                        self.result = if self.result == YesNoMessageBoxResult::Yes {
+                            YesNoMessageBoxResult::No
+                        } else {
+                            YesNoMessageBoxResult::Yes
                         }
-                        KeyCode::Enter => self.exit = true,
-                        KeyCode::Esc => {
-                            self.result = YesNoMessageBoxResult::No;
-                            self.exit = true;
-                        }
-                        _ => {}
                     }
+                    KeyCode::Enter => self.exit = true,
+                    KeyCode::Esc => {
+                        self.result = YesNoMessageBoxResult::No;
+                        self.exit = true;
+                    }
+                    _ => {}
                 }
             }
         }
@@ -267,14 +264,12 @@ impl<'a, T> Layer for CancelableMessageBoxLayer<'a, T> {
         }
 
         if !self.exit {
-            if let Some(event) = event {
-                if let Event::Key(key_event) = event {
-                    if key_event.kind == KeyEventKind::Press {
-                        match key_event.code {
-                            KeyCode::Enter => self.exit = true,
-                            KeyCode::Esc => self.exit = true,
-                            _ => {}
-                        }
+            if let Some(Event::Key(key_event)) = event {
+                if key_event.kind == KeyEventKind::Press {
+                    match key_event.code {
+                        KeyCode::Enter => self.exit = true,
+                        KeyCode::Esc => self.exit = true,
+                        _ => {}
                     }
                 }
             }
@@ -319,3 +314,20 @@ fn message_box_body<'a>(
 
     bottom
 }
+
+/// Shows warnings, like those returned by a server group update, if any.
+pub fn show_warnings(warnings: &[String]) -> std::io::Result<()> {
+    const MAX_LINES: usize = 10;
+    if warnings.is_empty() {
+        return Ok(());
+    }
+    let mut lines: Vec<String> = warnings.iter().take(MAX_LINES).cloned().collect();
+    if warnings.len() > MAX_LINES {
+        lines.push(format!("... and {} more", warnings.len() - MAX_LINES));
+    }
+    MessageBoxLayer::new("Warning", lines.join("\n"))
+        .yellow()
+        .on_gray()
+        .show()?;
+    Ok(())
+}
@@ -1,5 +1,15 @@
+mod acl;
+mod acl_edit;
+mod dns_settings;
+mod logpane;
 pub mod mainview;
 mod messagebox;
+mod settings;
 mod sslocal_download;
+mod sslocal_releases;
 mod sslocal_update;
+mod sslocal_versions;
+mod ssserver_edit;
+mod ssserver_export;
 mod ssserver_import;
+mod ssserver_paste;
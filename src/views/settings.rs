@@ -0,0 +1,227 @@
+use ratatui::{
+    crossterm::event::{Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Flex, Layout},
+    style::{palette::tailwind::*, Style, Styled, Stylize},
+    widgets::{Block, HighlightSpacing, Paragraph, Row, Table, TableState},
+};
+
+use super::messagebox::MessageBoxLayer;
+use crate::{
+    sslocal::{TUN_ADDRESS, TUN_NAME},
+    userdata::{Listener, ListenerProtocol},
+    Layer,
+};
+
+pub struct SettingsLayer {
+    exit: bool,
+    /// The first one is the main SOCKS5 listener and is always kept.
+    listeners: Vec<Listener>,
+    /// Ports as typed, checked on confirm.
+    ports: Vec<String>,
+    table_state: TableState,
+    selected_style: Style,
+    row_styles: [Style; 2],
+    pub result: Option<Vec<Listener>>,
+}
+
+impl SettingsLayer {
+    pub fn new(listeners: Vec<Listener>) -> Self {
+        let ports = listeners.iter().map(|l| l.port.to_string()).collect();
+        Self {
+            exit: false,
+            listeners,
+            ports,
+            table_state: TableState::default().with_selected(0),
+            selected_style: Style::default().fg(BLACK).bg(INDIGO.c400),
+            row_styles: [
+                Style::default().fg(WHITE).bg(GRAY.c950),
+                Style::default().fg(WHITE).bg(GRAY.c900),
+            ],
+            result: None,
+        }
+    }
+
+    fn selected(&self) -> usize {
+        self.table_state
+            .selected()
+            .unwrap_or(0)
+            .min(self.listeners.len() - 1)
+    }
+
+    fn add_listener(&mut self) {
+        let port = self
+            .listeners
+            .iter()
+            .map(|l| l.port)
+            .max()
+            .unwrap_or(10808)
+            .saturating_add(1);
+        self.listeners.push(Listener {
+            protocol: ListenerProtocol::Http,
+            port,
+            lan: false,
+            tproxy: false,
+        });
+        self.ports.push(port.to_string());
+        self.table_state.select(Some(self.listeners.len() - 1));
+    }
+
+    fn build(&self) -> anyhow::Result<Vec<Listener>> {
+        let mut listeners = self.listeners.clone();
+        for (listener, port) in listeners.iter_mut().zip(&self.ports) {
+            if listener.protocol == ListenerProtocol::Tun {
+                continue;
+            }
+            listener.port = match port.parse::<u16>() {
+                Ok(port) if port != 0 => port,
+                _ => anyhow::bail!("port must be between 1 and 65535"),
+            };
+        }
+        let (tun, listeners): (Vec<Listener>, Vec<Listener>) = listeners
+            .into_iter()
+            .partition(|l| l.protocol == ListenerProtocol::Tun);
+        if tun.len() > 1 {
+            anyhow::bail!("only one TUN listener is supported");
+        }
+        for (i, listener) in listeners.iter().enumerate() {
+            if listeners[..i].iter().any(|l| l.port == listener.port) {
+                anyhow::bail!("port {} is used by more than one listener", listener.port);
+            }
+        }
+        Ok(listeners.into_iter().chain(tun).collect())
+    }
+}
+
+impl Layer for SettingsLayer {
+    fn before_show(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn view(&mut self, frame: &mut ratatui::Frame) {
+        let [title_layout, table_layout, footer_layout] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .flex(Flex::Legacy)
+        .areas(frame.area());
+
+        let title = " Settings ";
+        let [title_layout] = Layout::horizontal([Constraint::Length(title.len() as u16 + 2)])
+            .flex(Flex::Center)
+            .areas(title_layout);
+        let title = Paragraph::new(title).centered().block(Block::bordered());
+        frame.render_widget(title, title_layout);
+
+        let header = Row::new(["Protocol", "Port", "LAN", "Options"]).white();
+        let rows = self.listeners.iter().enumerate().map(|(i, listener)| {
+            let protocol = if i == 0 {
+                format!("{} (main)", listener.protocol.name())
+            } else {
+                format!("< {} >", listener.protocol.name())
+            };
+            let (port, lan, options) = match listener.protocol {
+                ListenerProtocol::Tun => (
+                    "-".to_string(),
+                    "-",
+                    format!("{} {}", TUN_NAME, TUN_ADDRESS),
+                ),
+                ListenerProtocol::Redir if listener.tproxy => (
+                    self.ports[i].clone(),
+                    lan(listener),
+                    "TPROXY, TCP and UDP".to_string(),
+                ),
+                ListenerProtocol::Redir => (
+                    self.ports[i].clone(),
+                    lan(listener),
+                    "REDIRECT, TCP only".to_string(),
+                ),
+                _ => (self.ports[i].clone(), lan(listener), String::new()),
+            };
+            Row::new([protocol, port, lan.to_string(), options]).set_style(self.row_styles[i % 2])
+        });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(16),
+                Constraint::Length(8),
+                Constraint::Length(4),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(header)
+        .block(Block::bordered().title("Listeners"))
+        .highlight_style(self.selected_style)
+        .highlight_spacing(HighlightSpacing::Always);
+        frame.render_stateful_widget(table, table_layout, &mut self.table_state);
+
+        let footer = Paragraph::new(
+            "↑ | ↓ | Protocol (← →) | Port (0-9) | LAN (Space) | TPROXY (t) | Add (a) | Remove (d) | Confirm (Enter) | Exit (Esc)",
+        )
+        .centered();
+        frame.render_widget(footer, footer_layout);
+    }
+
+    fn update(&mut self, event: Option<Event>) -> std::io::Result<()> {
+        if let Some(Event::Key(key_event)) = event {
+            if key_event.kind == KeyEventKind::Press {
+                let i = self.selected();
+                match key_event.code {
+                    KeyCode::Esc => self.exit = true,
+                    KeyCode::Up => self.table_state.select_previous(),
+                    KeyCode::Down => self.table_state.select_next(),
+                    KeyCode::Left if i > 0 => {
+                        self.listeners[i].protocol = self.listeners[i].protocol.previous();
+                    }
+                    KeyCode::Right if i > 0 => {
+                        self.listeners[i].protocol = self.listeners[i].protocol.next();
+                    }
+                    KeyCode::Char(' ') => self.listeners[i].lan = !self.listeners[i].lan,
+                    KeyCode::Char('t') if self.listeners[i].protocol == ListenerProtocol::Redir => {
+                        self.listeners[i].tproxy = !self.listeners[i].tproxy;
+                    }
+                    KeyCode::Char(c) if c.is_ascii_digit() => self.ports[i].push(c),
+                    KeyCode::Backspace => {
+                        self.ports[i].pop();
+                    }
+                    KeyCode::Char('a') => self.add_listener(),
+                    KeyCode::Char('d') | KeyCode::Delete if i > 0 => {
+                        self.listeners.remove(i);
+                        self.ports.remove(i);
+                        self.table_state.select(Some(i - 1));
+                    }
+                    KeyCode::Enter => match self.build() {
+                        Ok(listeners) => {
+                            self.result = Some(listeners);
+                            self.exit = true;
+                        }
+                        Err(err) => {
+                            MessageBoxLayer::new("Error", err.to_string())
+                                .red()
+                                .on_gray()
+                                .show()?;
+                        }
+                    },
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn close(&mut self) {
+        self.exit = true;
+    }
+
+    fn is_exit(&self) -> bool {
+        self.exit
+    }
+}
+
+fn lan(listener: &Listener) -> &'static str {
+    if listener.lan {
+        "yes"
+    } else {
+        "no"
+    }
+}
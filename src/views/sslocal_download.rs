@@ -1,6 +1,7 @@
 use std::{
     sync::{
         atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc::RecvTimeoutError,
         Arc,
     },
     thread::JoinHandle,
@@ -23,6 +24,8 @@ use super::messagebox::MessageBoxLayer;
 
 pub struct SSLocalDownloadLayer {
     exit: bool,
+    /// The release tag, installed as `ss/<version>/`.
+    version: String,
     asset: Arc<Asset>,
     cancel_token: Arc<AtomicBool>,
     downloaded_size: Arc<AtomicUsize>,
@@ -32,7 +35,7 @@ pub struct SSLocalDownloadLayer {
 }
 
 impl SSLocalDownloadLayer {
-    pub fn new(asset: Asset) -> Self {
+    pub fn new(version: String, asset: Asset) -> Self {
         let asset = Arc::new(asset);
         let cancel_token = Arc::new(AtomicBool::new(false));
         let downloaded_size = Arc::new(AtomicUsize::new(0));
@@ -50,34 +53,48 @@ impl SSLocalDownloadLayer {
         });
 
         let asset_cloned = asset.clone();
+        let version_cloned = version.clone();
         let cancel_token_cloned = cancel_token.clone();
         let downloaded_size_cloned = downloaded_size.clone();
         let extract_task = std::thread::spawn(move || {
             let mut bytes = Vec::with_capacity(asset_cloned.size);
             loop {
                 if cancel_token_cloned.load(Ordering::Relaxed) {
-                    break;
+                    return Ok(());
                 }
                 if bytes.len() == asset_cloned.size {
                     break;
                 }
-                if let Ok(vec_u8) = rx.recv_timeout(Duration::from_millis(100)) {
-                    downloaded_size_cloned.fetch_add(vec_u8.len(), Ordering::Relaxed);
-                    bytes.extend_from_slice(&vec_u8);
+                match rx.recv_timeout(Duration::from_millis(100)) {
+                    Ok(vec_u8) => {
+                        downloaded_size_cloned.fetch_add(vec_u8.len(), Ordering::Relaxed);
+                        bytes.extend_from_slice(&vec_u8);
+                    }
+                    Err(RecvTimeoutError::Disconnected) => break,
+                    Err(RecvTimeoutError::Timeout) => {}
                 }
             }
-            if bytes.len() == asset_cloned.size {
-                if asset_cloned.name.ends_with(".zip") {
-                    SSLocalManager::extract_zip(&bytes)?;
-                } else if asset_cloned.name.ends_with(".tar.xz") {
-                    SSLocalManager::extract_tar_xz(&bytes)?;
-                }
+            if bytes.len() != asset_cloned.size {
+                anyhow::bail!(
+                    "download of '{}' incomplete, {} of {} bytes",
+                    asset_cloned.name,
+                    bytes.len(),
+                    asset_cloned.size
+                );
+            }
+            let digest = SSLocalManager::verify_sha256(&asset_cloned, &bytes)?;
+            if asset_cloned.name.ends_with(".zip") {
+                SSLocalManager::extract_zip(&version_cloned, &bytes)?;
+            } else if asset_cloned.name.ends_with(".tar.xz") {
+                SSLocalManager::extract_tar_xz(&version_cloned, &bytes)?;
             }
+            SSLocalManager::record_sha256(&version_cloned, &asset_cloned, &digest)?;
             Ok(())
         });
 
         Self {
             exit: false,
+            version,
             asset,
             cancel_token,
             downloaded_size,
@@ -132,35 +149,29 @@ impl Layer for SSLocalDownloadLayer {
         if self.download_task.as_ref().unwrap().is_finished()
             && self.extract_task.as_ref().unwrap().is_finished()
         {
-            self.result = SSLocalManager::find_sslocal()?;
-            if self.result.is_none() {
-                match self.download_task.take().unwrap().join() {
-                    Ok(ok) => {
-                        if let Err(err) = ok {
-                            MessageBoxLayer::new("Error", err.to_string())
-                                .red()
-                                .on_gray()
-                                .show()?;
-                        }
-                    }
-                    Err(err) => {
-                        MessageBoxLayer::new("Error", format!("{:?}", err))
-                            .red()
-                            .on_gray()
-                            .show()?;
-                    }
-                }
-                match self.extract_task.take().unwrap().join() {
-                    Ok(ok) => {
-                        if let Err(err) = ok {
-                            MessageBoxLayer::new("Error", err.to_string())
-                                .red()
-                                .on_gray()
-                                .show()?;
-                        }
-                    }
+            // a failed download or checksum must never fall back to whatever
+            // sslocal is already installed
+            let mut failed = false;
+            for task in [
+                self.download_task.take().unwrap(),
+                self.extract_task.take().unwrap(),
+            ] {
+                let message = match task.join() {
+                    Ok(Ok(())) => continue,
+                    Ok(Err(err)) => err.to_string(),
+                    Err(err) => format!("{:?}", err),
+                };
+                failed = true;
+                MessageBoxLayer::new("Error", message)
+                    .red()
+                    .on_gray()
+                    .show()?;
+            }
+            if !failed {
+                match SSLocalManager::activate_new(&self.version) {
+                    Ok(sslocal) => self.result = Some(sslocal),
                     Err(err) => {
-                        MessageBoxLayer::new("Error", format!("{:?}", err))
+                        MessageBoxLayer::new("Error", err.to_string())
                             .red()
                             .on_gray()
                             .show()?;
@@ -171,20 +182,18 @@ impl Layer for SSLocalDownloadLayer {
         }
 
         if !self.exit {
-            if let Some(event) = event {
-                if let Event::Key(key_event) = event {
-                    if key_event.kind == KeyEventKind::Press {
-                        match key_event.code {
-                            KeyCode::Enter => {
-                                self.cancel_token.store(true, Ordering::Relaxed);
-                                self.exit = true;
-                            }
-                            KeyCode::Esc => {
-                                self.cancel_token.store(true, Ordering::Relaxed);
-                                self.exit = true;
-                            }
-                            _ => {}
+            if let Some(Event::Key(key_event)) = event {
+                if key_event.kind == KeyEventKind::Press {
+                    match key_event.code {
+                        KeyCode::Enter => {
+                            self.cancel_token.store(true, Ordering::Relaxed);
+                            self.exit = true;
+                        }
+                        KeyCode::Esc => {
+                            self.cancel_token.store(true, Ordering::Relaxed);
+                            self.exit = true;
                         }
+                        _ => {}
                     }
                 }
             }
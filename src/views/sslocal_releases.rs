@@ -0,0 +1,227 @@
+use ratatui::{
+    crossterm::event::{Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::{palette::tailwind::*, Style, Styled, Stylize},
+    widgets::{Block, HighlightSpacing, Paragraph, Row, Table, TableState, Wrap},
+};
+use semver::Version;
+
+use super::{messagebox::YesNoMessageBoxLayer, sslocal_update::SSLocalUpdateLayer};
+use crate::{
+    sslocal::{Release, SSLocal},
+    Layer,
+};
+
+/// Lists recent sslocal releases with their notes, any of them can be
+/// installed, older ones included.
+pub struct SSLocalReleasesLayer {
+    exit: bool,
+    releases: Vec<Release>,
+    installed: Option<Version>,
+    show_prereleases: bool,
+    /// Indices into `releases` of the rows shown.
+    visible: Vec<usize>,
+    notes_scroll: u16,
+    selected_style: Style,
+    row_styles: [Style; 2],
+    table_state: TableState,
+    pub result: Option<SSLocal>,
+}
+
+impl SSLocalReleasesLayer {
+    pub fn new(releases: Vec<Release>, installed: Option<Version>) -> Self {
+        let mut layer = Self {
+            exit: false,
+            releases,
+            installed,
+            show_prereleases: false,
+            visible: Vec::new(),
+            notes_scroll: 0,
+            selected_style: Style::default().fg(BLACK).bg(INDIGO.c400),
+            row_styles: [
+                Style::default().fg(WHITE).bg(GRAY.c950),
+                Style::default().fg(WHITE).bg(GRAY.c900),
+            ],
+            table_state: TableState::default().with_selected(0),
+            result: None,
+        };
+        layer.filter();
+        layer
+    }
+
+    fn filter(&mut self) {
+        let selected = self.selected().map(|release| release.tag_name.clone());
+        self.visible = (0..self.releases.len())
+            .filter(|i| self.show_prereleases || !self.releases[*i].prerelease)
+            .collect();
+        let row = selected
+            .and_then(|tag| {
+                self.visible
+                    .iter()
+                    .position(|i| self.releases[*i].tag_name == tag)
+            })
+            .unwrap_or(0);
+        self.table_state.select(Some(row));
+        self.notes_scroll = 0;
+    }
+
+    fn selected(&self) -> Option<&Release> {
+        self.table_state
+            .selected()
+            .and_then(|row| self.visible.get(row))
+            .map(|i| &self.releases[*i])
+    }
+
+    /// How `release` relates to the installed sslocal.
+    fn status(&self, release: &Release) -> &'static str {
+        match (release.version(), &self.installed) {
+            (Some(version), Some(installed)) if version == *installed => "installed",
+            (Some(version), Some(installed)) if version > *installed => "newer",
+            (Some(_), Some(_)) => "older",
+            _ => "",
+        }
+    }
+
+    fn install(&mut self) -> std::io::Result<()> {
+        let Some(release) = self.selected().cloned() else {
+            return Ok(());
+        };
+        if self.status(&release) == "older" {
+            let yes_no = YesNoMessageBoxLayer::new(
+                "Info",
+                format!(
+                    "downgrade sslocal from {} to {} ?",
+                    self.installed.as_ref().unwrap(),
+                    release.tag_name
+                ),
+            )
+            .red()
+            .on_gray()
+            .show()?;
+            if !yes_no.result.is_yes() {
+                return Ok(());
+            }
+        }
+        let update = SSLocalUpdateLayer::new(release).show()?;
+        if update.result.is_some() {
+            self.result = update.result;
+            self.exit = true;
+        }
+        Ok(())
+    }
+}
+
+impl Layer for SSLocalReleasesLayer {
+    fn before_show(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn view(&mut self, frame: &mut ratatui::Frame) {
+        let [table_layout, notes_layout, footer_layout] = Layout::vertical([
+            Constraint::Percentage(40),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let toggle = if self.show_prereleases {
+            "Hide Pre-releases (p)"
+        } else {
+            "Show Pre-releases (p)"
+        };
+        let footer = Paragraph::new(format!(
+            "Up (↑) | Down (↓) | Scroll Notes (PgUp PgDn) | {} | Install (Enter) | Exit (Esc)",
+            toggle
+        ))
+        .white()
+        .on_cyan()
+        .centered();
+        frame.render_widget(footer, footer_layout);
+
+        let header = Row::new(["Tag", "Published", "Kind", "Assets", "Installed"])
+            .white()
+            .on_blue();
+        let rows = self.visible.iter().enumerate().map(|(row, i)| {
+            let release = &self.releases[*i];
+            let kind = if release.prerelease {
+                "pre-release"
+            } else {
+                "release"
+            };
+            let mut style = self.row_styles[row % 2];
+            if self.status(release) == "installed" {
+                style = style.fg(GREEN.c400).bold();
+            }
+            Row::new([
+                release.tag_name.clone(),
+                release.published_date().to_string(),
+                kind.to_string(),
+                release.assets.len().to_string(),
+                self.status(release).to_string(),
+            ])
+            .set_style(style)
+        });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Fill(1),
+                Constraint::Length(12),
+                Constraint::Length(12),
+                Constraint::Length(7),
+                Constraint::Length(10),
+            ],
+        )
+        .header(header)
+        .highlight_style(self.selected_style)
+        .highlight_spacing(HighlightSpacing::Always);
+        frame.render_stateful_widget(table, table_layout, &mut self.table_state);
+
+        let (title, notes) = match self.selected() {
+            Some(release) => (
+                format!("Release Notes {}", release.tag_name),
+                release.body.clone().unwrap_or_default().replace('\r', ""),
+            ),
+            None => ("Release Notes".to_string(), String::new()),
+        };
+        let notes = Paragraph::new(notes)
+            .block(Block::bordered().title(title))
+            .wrap(Wrap { trim: false })
+            .scroll((self.notes_scroll, 0));
+        frame.render_widget(notes, notes_layout);
+    }
+
+    fn update(&mut self, event: Option<Event>) -> std::io::Result<()> {
+        if let Some(Event::Key(key_event)) = event {
+            if key_event.kind == KeyEventKind::Press {
+                match key_event.code {
+                    KeyCode::Esc => self.exit = true,
+                    KeyCode::Up => {
+                        self.table_state.select_previous();
+                        self.notes_scroll = 0;
+                    }
+                    KeyCode::Down => {
+                        self.table_state.select_next();
+                        self.notes_scroll = 0;
+                    }
+                    KeyCode::PageUp => self.notes_scroll = self.notes_scroll.saturating_sub(10),
+                    KeyCode::PageDown => self.notes_scroll = self.notes_scroll.saturating_add(10),
+                    KeyCode::Char('p') => {
+                        self.show_prereleases = !self.show_prereleases;
+                        self.filter();
+                    }
+                    KeyCode::Enter => self.install()?,
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn close(&mut self) {
+        self.exit = true;
+    }
+
+    fn is_exit(&self) -> bool {
+        self.exit
+    }
+}
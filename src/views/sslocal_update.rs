@@ -10,16 +10,23 @@ use super::{
     sslocal_download::SSLocalDownloadLayer,
 };
 use crate::{
-    sslocal::{LatestRelease, SSLocal},
+    sslocal::{Release, SSLocal, Target},
     Layer,
 };
 
 pub struct SSLocalUpdateLayer {
     exit: bool,
-    latest: LatestRelease,
+    release: Release,
     longest_name_len: u16,
     longest_size_str_len: u16,
     row_str_vec: Vec<(String, String, String)>,
+    /// Index into `release.assets` of the archive built for this machine.
+    matching: Option<usize>,
+    compatible: Vec<bool>,
+    /// Show the assets built for other platforms too.
+    show_all: bool,
+    /// Indices into `release.assets` of the rows shown.
+    visible: Vec<usize>,
     selected_style: Style,
     row_styles: [Style; 2],
     table_state: TableState,
@@ -27,20 +34,20 @@ pub struct SSLocalUpdateLayer {
 }
 
 impl SSLocalUpdateLayer {
-    pub fn new(latest: LatestRelease) -> Self {
-        let longest_name_len = latest
+    pub fn new(release: Release) -> Self {
+        let longest_name_len = release
             .assets
             .iter()
             .map(|asset| asset.name.len())
             .max()
             .unwrap_or(0) as u16;
-        let size_str_vec: Vec<String> = latest
+        let size_str_vec: Vec<String> = release
             .assets
             .iter()
             .map(|asset| format!("{:.2} MB", asset.size as f32 / 1024.0 / 1024.0))
             .collect();
         let longest_size_str_len = size_str_vec.iter().map(|s| s.len()).max().unwrap_or(0) as u16;
-        let row_str_vec: Vec<_> = latest
+        let row_str_vec: Vec<_> = release
             .assets
             .iter()
             .enumerate()
@@ -60,17 +67,54 @@ impl SSLocalUpdateLayer {
         ];
         let table_state = TableState::default().with_selected(0);
 
-        Self {
+        let target = Target::current();
+        let compatible: Vec<bool> = release
+            .assets
+            .iter()
+            .map(|asset| asset.target().is_some_and(|t| target.is_compatible(t)))
+            .collect();
+        let matching = release.matching_asset().and_then(|matching| {
+            release
+                .assets
+                .iter()
+                .position(|asset| asset.name == matching.name)
+        });
+
+        let mut layer = Self {
             exit: false,
-            latest,
+            release,
             longest_name_len,
             longest_size_str_len,
             row_str_vec,
+            matching,
+            // nothing to hide if nothing matches
+            show_all: !compatible.contains(&true),
+            compatible,
+            visible: Vec::new(),
             selected_style,
             row_styles,
             table_state,
             result: None,
-        }
+        };
+        layer.filter();
+        layer
+    }
+
+    /// Rebuilds the visible rows, keeping the selected asset or else picking
+    /// the matching one.
+    fn filter(&mut self) {
+        let selected = self
+            .table_state
+            .selected()
+            .and_then(|i| self.visible.get(i).copied())
+            .or(self.matching);
+        self.visible = (0..self.release.assets.len())
+            .filter(|i| self.show_all || self.compatible[*i])
+            .collect();
+        let row = selected
+            .and_then(|selected| self.visible.iter().position(|i| *i == selected))
+            .unwrap_or(0);
+        self.table_state.select(Some(row));
     }
 }
 
@@ -80,21 +124,31 @@ impl Layer for SSLocalUpdateLayer {
             Layout::vertical([Constraint::Percentage(100), Constraint::Length(1)])
                 .areas(frame.area());
 
-        let footer = Paragraph::new("Up (↑) | Down (↓) | Select (Enter) | Exit (Esc)")
-            .white()
-            .on_cyan()
-            .centered();
+        let toggle = if self.show_all {
+            "Only For This Machine (a)"
+        } else {
+            "Show All (a)"
+        };
+        let footer = Paragraph::new(format!(
+            "Up (↑) | Down (↓) | Select (Enter) | {} | Exit (Esc)",
+            toggle
+        ))
+        .white()
+        .on_cyan()
+        .centered();
         frame.render_widget(footer, footer_layout);
 
         let header = Row::new(["Name", "Size", "Download Url"]).white().on_blue();
-        let rows = self
-            .row_str_vec
-            .iter()
-            .enumerate()
-            .map(|(i, (name, size, url))| {
-                Row::new([name.as_str(), size.as_str(), url.as_str()])
-                    .set_style(self.row_styles[i % 2])
-            });
+        let rows = self.visible.iter().enumerate().map(|(row, i)| {
+            let (name, size, url) = &self.row_str_vec[*i];
+            let mut style = self.row_styles[row % 2];
+            if Some(*i) == self.matching {
+                style = style.fg(GREEN.c400).bold();
+            } else if !self.compatible[*i] {
+                style = style.fg(GRAY.c500);
+            }
+            Row::new([name.as_str(), size.as_str(), url.as_str()]).set_style(style)
+        });
         let table = Table::new(
             rows,
             [
@@ -110,49 +164,66 @@ impl Layer for SSLocalUpdateLayer {
     }
 
     fn before_show(&mut self) -> std::io::Result<()> {
+        if self.matching.is_none() {
+            MessageBoxLayer::new(
+                "Info",
+                format!(
+                    "no archive found for '{}', pick one by hand",
+                    Target::current().name()
+                ),
+            )
+            .green()
+            .on_gray()
+            .show()?;
+        }
         Ok(())
     }
 
     fn update(&mut self, event: Option<ratatui::crossterm::event::Event>) -> std::io::Result<()> {
-        if let Some(event) = event {
-            if let Event::Key(key_event) = event {
-                if key_event.kind == KeyEventKind::Press {
-                    match key_event.code {
-                        KeyCode::Esc => self.exit = true,
-                        KeyCode::Up => self.table_state.select_previous(),
-                        KeyCode::Down => self.table_state.select_next(),
-                        KeyCode::Enter => {
-                            if let Some(i) = self.table_state.selected() {
-                                if self.latest.assets[i].name.ends_with(".zip")
-                                    || self.latest.assets[i].name.ends_with(".tar.xz")
-                                {
-                                    let yes_no = YesNoMessageBoxLayer::new(
-                                        "Info",
-                                        format!("download '{}' ?", self.latest.assets[i].name),
+        if let Some(Event::Key(key_event)) = event {
+            if key_event.kind == KeyEventKind::Press {
+                match key_event.code {
+                    KeyCode::Esc => self.exit = true,
+                    KeyCode::Up => self.table_state.select_previous(),
+                    KeyCode::Down => self.table_state.select_next(),
+                    KeyCode::Char('a') => {
+                        self.show_all = !self.show_all;
+                        self.filter();
+                    }
+                    KeyCode::Enter => {
+                        if let Some(&i) = self
+                            .table_state
+                            .selected()
+                            .and_then(|row| self.visible.get(row))
+                        {
+                            if self.release.assets[i].is_archive() {
+                                let yes_no = YesNoMessageBoxLayer::new(
+                                    "Info",
+                                    format!("download '{}' ?", self.release.assets[i].name),
+                                )
+                                .green()
+                                .on_gray()
+                                .show()?;
+                                if yes_no.result.is_yes() {
+                                    let download = SSLocalDownloadLayer::new(
+                                        self.release.tag_name.clone(),
+                                        self.release.assets[i].clone(),
                                     )
-                                    .green()
-                                    .on_gray()
                                     .show()?;
-                                    if yes_no.result.is_yes() {
-                                        let download = SSLocalDownloadLayer::new(
-                                            self.latest.assets[i].clone(),
-                                        )
-                                        .show()?;
-                                        if download.result.is_some() {
-                                            self.result = download.result;
-                                            self.exit = true;
-                                        }
+                                    if download.result.is_some() {
+                                        self.result = download.result;
+                                        self.exit = true;
                                     }
-                                } else {
-                                    MessageBoxLayer::new("Error", "unsupported file type")
-                                        .red()
-                                        .on_gray()
-                                        .show()?;
                                 }
+                            } else {
+                                MessageBoxLayer::new("Error", "unsupported file type")
+                                    .red()
+                                    .on_gray()
+                                    .show()?;
                             }
                         }
-                        _ => {}
                     }
+                    _ => {}
                 }
             }
         }
@@ -0,0 +1,195 @@
+use ratatui::{
+    crossterm::event::{Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Flex, Layout},
+    style::{palette::tailwind::*, Style, Styled, Stylize},
+    widgets::{Block, HighlightSpacing, Paragraph, Row, Table, TableState},
+};
+
+use super::messagebox::{MessageBoxLayer, YesNoMessageBoxLayer};
+use crate::{
+    sslocal::{InstalledVersions, SSLocal, SSLocalManager},
+    Layer,
+};
+
+/// Lists the installed sslocal versions and picks the one that is used.
+pub struct SSLocalVersionsLayer {
+    exit: bool,
+    installed: Vec<String>,
+    active: Option<String>,
+    versions: InstalledVersions,
+    table_state: TableState,
+    selected_style: Style,
+    row_styles: [Style; 2],
+    /// The sslocal to use from now on, if it changed.
+    pub result: Option<SSLocal>,
+}
+
+impl Default for SSLocalVersionsLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SSLocalVersionsLayer {
+    pub fn new() -> Self {
+        Self {
+            exit: false,
+            installed: Vec::new(),
+            active: None,
+            versions: InstalledVersions::default(),
+            table_state: TableState::default().with_selected(0),
+            selected_style: Style::default().fg(BLACK).bg(INDIGO.c400),
+            row_styles: [
+                Style::default().fg(WHITE).bg(GRAY.c950),
+                Style::default().fg(WHITE).bg(GRAY.c900),
+            ],
+            result: None,
+        }
+    }
+
+    fn selected(&self) -> Option<String> {
+        self.table_state
+            .selected()
+            .and_then(|i| self.installed.get(i))
+            .cloned()
+    }
+
+    fn show_error(message: String) -> std::io::Result<()> {
+        MessageBoxLayer::new("Error", message)
+            .red()
+            .on_gray()
+            .show()?;
+        Ok(())
+    }
+
+    fn reload(&mut self) -> std::io::Result<()> {
+        self.installed = SSLocalManager::installed()?;
+        self.active = SSLocalManager::active_version()?;
+        self.versions = SSLocalManager::load_versions()?;
+        Ok(())
+    }
+
+    fn switch(&mut self, result: anyhow::Result<SSLocal>) -> std::io::Result<()> {
+        match result {
+            Ok(sslocal) => {
+                MessageBoxLayer::new("Info", format!("now using {}", sslocal.version.trim()))
+                    .green()
+                    .on_gray()
+                    .show()?;
+                self.result = Some(sslocal);
+            }
+            Err(err) => Self::show_error(err.to_string())?,
+        }
+        self.reload()
+    }
+
+    fn uninstall(&mut self) -> std::io::Result<()> {
+        let Some(version) = self.selected() else {
+            return Ok(());
+        };
+        let yes_no = YesNoMessageBoxLayer::new("Info", format!("remove sslocal '{}' ?", version))
+            .red()
+            .on_gray()
+            .show()?;
+        if yes_no.result.is_yes() {
+            if let Err(err) = SSLocalManager::uninstall(&version) {
+                Self::show_error(err.to_string())?;
+            }
+            self.reload()?;
+        }
+        Ok(())
+    }
+}
+
+impl Layer for SSLocalVersionsLayer {
+    fn before_show(&mut self) -> std::io::Result<()> {
+        self.reload()
+    }
+
+    fn view(&mut self, frame: &mut ratatui::Frame) {
+        let [title_layout, table_layout, footer_layout] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .flex(Flex::Legacy)
+        .areas(frame.area());
+
+        let title = " Installed SSLocal ";
+        let [title_layout] = Layout::horizontal([Constraint::Length(title.len() as u16 + 2)])
+            .flex(Flex::Center)
+            .areas(title_layout);
+        let title = Paragraph::new(title).centered().block(Block::bordered());
+        frame.render_widget(title, title_layout);
+
+        let header = Row::new(["Version", "State", "Location"]).white();
+        let rows = self.installed.iter().enumerate().map(|(i, version)| {
+            let state = if self.active.as_ref() == Some(version) {
+                "active"
+            } else if self.versions.previous.as_ref() == Some(version) {
+                "previous"
+            } else {
+                ""
+            };
+            let location = if version == SSLocalManager::LEGACY {
+                "ss/".to_string()
+            } else {
+                format!("ss/{}/", version)
+            };
+            let mut style = self.row_styles[i % 2];
+            if state == "active" {
+                style = style.fg(GREEN.c400).bold();
+            }
+            Row::new([version.clone(), state.to_string(), location]).set_style(style)
+        });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Fill(1),
+                Constraint::Length(10),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(header)
+        .block(Block::bordered())
+        .highlight_style(self.selected_style)
+        .highlight_spacing(HighlightSpacing::Always);
+        frame.render_stateful_widget(table, table_layout, &mut self.table_state);
+
+        let footer =
+            Paragraph::new("↑ | ↓ | Use (Enter) | Roll Back (b) | Remove (Del) | Exit (Esc)")
+                .centered();
+        frame.render_widget(footer, footer_layout);
+    }
+
+    fn update(&mut self, event: Option<Event>) -> std::io::Result<()> {
+        if let Some(Event::Key(key_event)) = event {
+            if key_event.kind == KeyEventKind::Press {
+                match key_event.code {
+                    KeyCode::Esc => self.exit = true,
+                    KeyCode::Up => self.table_state.select_previous(),
+                    KeyCode::Down => self.table_state.select_next(),
+                    KeyCode::Enter => {
+                        if let Some(version) = self.selected() {
+                            if self.active.as_ref() != Some(&version) {
+                                self.switch(SSLocalManager::activate(&version))?;
+                            }
+                        }
+                    }
+                    KeyCode::Char('b') => self.switch(SSLocalManager::rollback())?,
+                    KeyCode::Delete => self.uninstall()?,
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn close(&mut self) {
+        self.exit = true;
+    }
+
+    fn is_exit(&self) -> bool {
+        self.exit
+    }
+}
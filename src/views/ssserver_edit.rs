@@ -0,0 +1,257 @@
+use ratatui::{
+    crossterm::event::{Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Flex, Layout},
+    style::Stylize,
+    widgets::{Block, Paragraph},
+};
+
+use super::messagebox::MessageBoxLayer;
+use crate::{
+    userdata::{SSServer, METHODS},
+    Layer,
+};
+
+#[derive(Clone, Copy, PartialEq)]
+enum Field {
+    Remarks,
+    Server,
+    Port,
+    Method,
+    Password,
+    Plugin,
+    PluginOpts,
+}
+
+impl Field {
+    const ALL: [Field; 7] = [
+        Field::Remarks,
+        Field::Server,
+        Field::Port,
+        Field::Method,
+        Field::Password,
+        Field::Plugin,
+        Field::PluginOpts,
+    ];
+
+    fn title(&self) -> &'static str {
+        match self {
+            Field::Remarks => "Name",
+            Field::Server => "Server",
+            Field::Port => "Port",
+            Field::Method => "Method (← →)",
+            Field::Password => "Password",
+            Field::Plugin => "Plugin",
+            Field::PluginOpts => "Plugin Options",
+        }
+    }
+
+    fn next(self) -> Self {
+        let i = Field::ALL.iter().position(|f| *f == self).unwrap();
+        Field::ALL[(i + 1) % Field::ALL.len()]
+    }
+
+    fn previous(self) -> Self {
+        let i = Field::ALL.iter().position(|f| *f == self).unwrap();
+        Field::ALL[(i + Field::ALL.len() - 1) % Field::ALL.len()]
+    }
+}
+
+pub struct SSServerEditLayer {
+    exit: bool,
+    title: String,
+    id: Option<String>,
+    remarks: String,
+    server: String,
+    port: String,
+    /// `METHODS`, plus the server's own method if it isn't one of them, so
+    /// editing never changes the cipher behind the user's back.
+    methods: Vec<String>,
+    method_index: usize,
+    password: String,
+    plugin: String,
+    plugin_opts: String,
+    field: Field,
+    pub result: Option<SSServer>,
+}
+
+impl SSServerEditLayer {
+    pub fn new(server: Option<&SSServer>) -> Self {
+        let title = if server.is_some() {
+            " Edit Server "
+        } else {
+            " Add Server "
+        };
+        let mut methods: Vec<String> = METHODS.iter().map(|m| m.to_string()).collect();
+        let method_index = match server {
+            Some(server) => match methods.iter().position(|m| *m == server.method) {
+                Some(i) => i,
+                None => {
+                    methods.push(server.method.clone());
+                    methods.len() - 1
+                }
+            },
+            None => 0,
+        };
+        Self {
+            exit: false,
+            title: title.to_string(),
+            id: server.and_then(|server| server.id.clone()),
+            remarks: server.map(|s| s.remarks.clone()).unwrap_or_default(),
+            server: server.map(|s| s.server.clone()).unwrap_or_default(),
+            port: server
+                .map(|s| s.server_port.to_string())
+                .unwrap_or_default(),
+            methods,
+            method_index,
+            password: server.map(|s| s.password.clone()).unwrap_or_default(),
+            plugin: server.and_then(|s| s.plugin.clone()).unwrap_or_default(),
+            plugin_opts: server
+                .and_then(|s| s.plugin_opts.clone())
+                .unwrap_or_default(),
+            field: Field::Remarks,
+            result: None,
+        }
+    }
+
+    fn text_mut(&mut self) -> Option<&mut String> {
+        match self.field {
+            Field::Remarks => Some(&mut self.remarks),
+            Field::Server => Some(&mut self.server),
+            Field::Port => Some(&mut self.port),
+            Field::Method => None,
+            Field::Password => Some(&mut self.password),
+            Field::Plugin => Some(&mut self.plugin),
+            Field::PluginOpts => Some(&mut self.plugin_opts),
+        }
+    }
+
+    fn value(&self, field: Field) -> String {
+        match field {
+            Field::Remarks => self.remarks.clone(),
+            Field::Server => self.server.clone(),
+            Field::Port => self.port.clone(),
+            Field::Method => format!("< {} >", self.methods[self.method_index]),
+            Field::Password => self.password.clone(),
+            Field::Plugin => self.plugin.clone(),
+            Field::PluginOpts => self.plugin_opts.clone(),
+        }
+    }
+
+    fn build(&self) -> anyhow::Result<SSServer> {
+        let server_port = match self.port.trim().parse::<u16>() {
+            Ok(port) => port,
+            Err(_) => anyhow::bail!("port must be between 1 and 65535"),
+        };
+        let optional = |s: &str| {
+            let s = s.trim();
+            (!s.is_empty()).then(|| s.to_string())
+        };
+        let server = SSServer {
+            id: self.id.clone(),
+            remarks: self.remarks.trim().to_string(),
+            server: self.server.trim().to_string(),
+            server_port,
+            method: self.methods[self.method_index].clone(),
+            password: self.password.clone(),
+            plugin: optional(&self.plugin),
+            plugin_opts: optional(&self.plugin_opts),
+            latency: None,
+        };
+        server.validate()?;
+        Ok(server)
+    }
+}
+
+impl Layer for SSServerEditLayer {
+    fn before_show(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn view(&mut self, frame: &mut ratatui::Frame) {
+        let mut constraints = vec![Constraint::Length(3)];
+        constraints.extend(Field::ALL.iter().map(|_| Constraint::Length(3)));
+        constraints.push(Constraint::Min(0));
+        constraints.push(Constraint::Length(1));
+        let layouts = Layout::vertical(constraints)
+            .flex(Flex::Legacy)
+            .split(frame.area());
+
+        let [title_layout] = Layout::horizontal([Constraint::Length(self.title.len() as u16 + 2)])
+            .flex(Flex::Center)
+            .areas(layouts[0]);
+        let title = Paragraph::new(self.title.as_str())
+            .centered()
+            .block(Block::bordered());
+        frame.render_widget(title, title_layout);
+
+        for (i, field) in Field::ALL.iter().enumerate() {
+            let mut paragraph =
+                Paragraph::new(self.value(*field)).block(Block::bordered().title(field.title()));
+            if *field == self.field {
+                paragraph = paragraph.green();
+            }
+            frame.render_widget(paragraph, layouts[i + 1]);
+        }
+
+        let footer =
+            Paragraph::new("Next (Tab/↓) | Prev (↑) | Clear (Del) | Confirm (Enter) | Exit (Esc)")
+                .centered();
+        frame.render_widget(footer, layouts[layouts.len() - 1]);
+    }
+
+    fn update(&mut self, event: Option<Event>) -> std::io::Result<()> {
+        if let Some(Event::Key(key_event)) = event {
+            if key_event.kind == KeyEventKind::Press {
+                match key_event.code {
+                    KeyCode::Esc => self.exit = true,
+                    KeyCode::Tab | KeyCode::Down => self.field = self.field.next(),
+                    KeyCode::BackTab | KeyCode::Up => self.field = self.field.previous(),
+                    KeyCode::Left if self.field == Field::Method => {
+                        self.method_index =
+                            (self.method_index + self.methods.len() - 1) % self.methods.len();
+                    }
+                    KeyCode::Right if self.field == Field::Method => {
+                        self.method_index = (self.method_index + 1) % self.methods.len();
+                    }
+                    KeyCode::Delete => {
+                        if let Some(text) = self.text_mut() {
+                            text.clear();
+                        }
+                    }
+                    KeyCode::Backspace => {
+                        if let Some(text) = self.text_mut() {
+                            text.pop();
+                        }
+                    }
+                    KeyCode::Char(c) => {
+                        if let Some(text) = self.text_mut() {
+                            text.push(c);
+                        }
+                    }
+                    KeyCode::Enter => match self.build() {
+                        Ok(server) => {
+                            self.result = Some(server);
+                            self.exit = true;
+                        }
+                        Err(err) => {
+                            MessageBoxLayer::new("Error", err.to_string())
+                                .red()
+                                .on_gray()
+                                .show()?;
+                        }
+                    },
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn close(&mut self) {
+        self.exit = true;
+    }
+
+    fn is_exit(&self) -> bool {
+        self.exit
+    }
+}
@@ -0,0 +1,109 @@
+use qrcode::QrCode;
+use ratatui::{
+    crossterm::event::{Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Flex, Layout},
+    style::Stylize,
+    widgets::{Block, Paragraph, Wrap},
+};
+
+use crate::{userdata::SSServer, widgets::QrCodeWidget, Layer};
+
+pub struct SSServerExportLayer {
+    exit: bool,
+    title: String,
+    ssurl: String,
+    qrcode: Option<QrCode>,
+}
+
+impl SSServerExportLayer {
+    pub fn new(server: &SSServer) -> Self {
+        let ssurl = server.to_ssurl();
+        let qrcode = QrCode::new(&ssurl).ok();
+        Self {
+            exit: false,
+            title: format!(" Share '{}' ", server.remarks),
+            ssurl,
+            qrcode,
+        }
+    }
+}
+
+impl Layer for SSServerExportLayer {
+    fn before_show(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn view(&mut self, frame: &mut ratatui::Frame) {
+        let [title_layout, url_layout, qrcode_layout, footer_layout] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Length(5),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .flex(Flex::Legacy)
+        .areas(frame.area());
+
+        let [title_layout] = Layout::horizontal([Constraint::Length(self.title.len() as u16 + 2)])
+            .flex(Flex::Center)
+            .areas(title_layout);
+        let title = Paragraph::new(self.title.as_str())
+            .centered()
+            .block(Block::bordered());
+        frame.render_widget(title, title_layout);
+
+        let url = Paragraph::new(self.ssurl.as_str())
+            .block(Block::bordered().title("ss:// Link"))
+            .wrap(Wrap { trim: true });
+        frame.render_widget(url, url_layout);
+
+        match self.qrcode.clone().map(QrCodeWidget::new) {
+            Some(qrcode) => {
+                let (width, height) = qrcode.size();
+                if width > qrcode_layout.width || height > qrcode_layout.height {
+                    let message = Paragraph::new("terminal too small for the QR code")
+                        .centered()
+                        .yellow();
+                    frame.render_widget(message, qrcode_layout);
+                } else {
+                    let [qrcode_layout] = Layout::vertical([Constraint::Length(height)])
+                        .flex(Flex::Center)
+                        .areas(qrcode_layout);
+                    let [qrcode_layout] = Layout::horizontal([Constraint::Length(width)])
+                        .flex(Flex::Center)
+                        .areas(qrcode_layout);
+                    frame.render_widget(qrcode, qrcode_layout);
+                }
+            }
+            None => {
+                let message = Paragraph::new("link too long for a QR code")
+                    .centered()
+                    .red();
+                frame.render_widget(message, qrcode_layout);
+            }
+        }
+
+        let footer = Paragraph::new("Exit (Esc)").centered();
+        frame.render_widget(footer, footer_layout);
+    }
+
+    fn update(&mut self, event: Option<Event>) -> std::io::Result<()> {
+        if let Some(Event::Key(key_event)) = event {
+            if key_event.kind == KeyEventKind::Press {
+                match key_event.code {
+                    KeyCode::Esc => self.exit = true,
+                    KeyCode::Enter => self.exit = true,
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn close(&mut self) {
+        self.exit = true;
+    }
+
+    fn is_exit(&self) -> bool {
+        self.exit
+    }
+}
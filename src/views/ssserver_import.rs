@@ -5,7 +5,7 @@ use ratatui::{
     widgets::{Block, Paragraph, Wrap},
 };
 
-use super::messagebox::MessageBoxLayer;
+use super::messagebox::{show_warnings, MessageBoxLayer};
 use crate::{userdata::ServerGroup, Layer};
 
 enum State {
@@ -67,7 +67,7 @@ impl Layer for SSServerImportLayer {
         frame.render_widget(name, name_layout);
 
         let mut url = Paragraph::new(self.url.as_str())
-            .block(Block::bordered().title("Update URL"))
+            .block(Block::bordered().title("Update URL / File Path (empty for a manual group)"))
             .wrap(Wrap { trim: true });
         if let State::Url = self.state {
             url = url.green();
@@ -76,50 +76,59 @@ impl Layer for SSServerImportLayer {
     }
 
     fn update(&mut self, event: Option<ratatui::crossterm::event::Event>) -> std::io::Result<()> {
-        if let Some(event) = event {
-            if let Event::Key(key_event) = event {
-                if key_event.kind == KeyEventKind::Press {
-                    match key_event.code {
-                        KeyCode::Esc => self.exit = true,
-                        KeyCode::Delete => {
-                            match self.state {
-                                State::Name => self.name.clear(),
-                                State::Url => self.url.clear(),
-                            };
-                        }
-                        KeyCode::Backspace => {
-                            match self.state {
-                                State::Name => self.name.pop(),
-                                State::Url => self.url.pop(),
-                            };
-                        }
-                        KeyCode::Tab => {
-                            self.state = match self.state {
-                                State::Name => State::Url,
-                                State::Url => State::Name,
-                            };
-                        }
-                        KeyCode::Char(c) => match self.state {
-                            State::Name => self.name.push(c),
-                            State::Url => self.url.push(c),
-                        },
-                        KeyCode::Enter => {
-                            let mut result = ServerGroup::new(&self.name, &self.url);
-                            match result.update() {
-                                Ok(_) => {
-                                    self.result = Some(result);
-                                    self.exit = true;
-                                }
-                                Err(e) => {
-                                    MessageBoxLayer::new("Error", e.to_string())
-                                        .red()
-                                        .on_gray()
-                                        .show()?;
-                                }
+        if let Some(Event::Paste(text)) = &event {
+            match self.state {
+                State::Name => self.name.push_str(text.trim()),
+                State::Url => self.url.push_str(text.trim()),
+            }
+        }
+        if let Some(Event::Key(key_event)) = event {
+            if key_event.kind == KeyEventKind::Press {
+                match key_event.code {
+                    KeyCode::Esc => self.exit = true,
+                    KeyCode::Delete => {
+                        match self.state {
+                            State::Name => self.name.clear(),
+                            State::Url => self.url.clear(),
+                        };
+                    }
+                    KeyCode::Backspace => {
+                        match self.state {
+                            State::Name => self.name.pop(),
+                            State::Url => self.url.pop(),
+                        };
+                    }
+                    KeyCode::Tab => {
+                        self.state = match self.state {
+                            State::Name => State::Url,
+                            State::Url => State::Name,
+                        };
+                    }
+                    KeyCode::Char(c) => match self.state {
+                        State::Name => self.name.push(c),
+                        State::Url => self.url.push(c),
+                    },
+                    KeyCode::Enter if self.url.trim().is_empty() => {
+                        self.result = Some(ServerGroup::manual(&self.name));
+                        self.exit = true;
+                    }
+                    KeyCode::Enter => {
+                        let mut result = ServerGroup::new(&self.name, &self.url);
+                        match result.update() {
+                            Ok(warnings) => {
+                                show_warnings(&warnings)?;
+                                self.result = Some(result);
+                                self.exit = true;
+                            }
+                            Err(e) => {
+                                MessageBoxLayer::new("Error", e.to_string())
+                                    .red()
+                                    .on_gray()
+                                    .show()?;
                             }
                         }
-                        _ => {}
                     }
+                    _ => {}
                 }
             }
         }
@@ -0,0 +1,258 @@
+use ratatui::{
+    crossterm::event::{Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Flex, Layout},
+    style::{palette::tailwind::*, Style, Styled, Stylize},
+    text::Line,
+    widgets::{Block, Paragraph, Row, Table, Wrap},
+};
+
+use super::messagebox::MessageBoxLayer;
+use crate::{
+    userdata::{parse_ssurl_lines, SSServer},
+    Layer,
+};
+
+enum State {
+    Links,
+    Name,
+}
+
+pub enum PasteTarget {
+    NewGroup(String),
+    Group(usize),
+}
+
+pub struct SSServerPasteLayer {
+    exit: bool,
+    text: String,
+    name: String,
+    state: State,
+    /// Manual groups the servers can be appended to, as (group index, name).
+    groups: Vec<(usize, String)>,
+    /// 0 is a new group, `i` is `groups[i - 1]`.
+    target_index: usize,
+    parsed: Vec<(usize, anyhow::Result<SSServer>)>,
+    row_styles: [Style; 2],
+    pub result: Option<(PasteTarget, Vec<SSServer>)>,
+}
+
+impl SSServerPasteLayer {
+    pub fn new<S: Into<String>>(groups: Vec<(usize, String)>, text: S) -> Self {
+        let mut layer = Self {
+            exit: false,
+            text: text.into(),
+            name: "Pasted".to_string(),
+            state: State::Links,
+            groups,
+            target_index: 0,
+            parsed: Vec::new(),
+            row_styles: [
+                Style::default().fg(WHITE).bg(GRAY.c950),
+                Style::default().fg(WHITE).bg(GRAY.c900),
+            ],
+            result: None,
+        };
+        layer.parse();
+        layer
+    }
+
+    fn parse(&mut self) {
+        self.parsed = parse_ssurl_lines(&self.text);
+    }
+
+    fn target_str(&self) -> String {
+        if self.target_index == 0 {
+            format!("< new group '{}' >", self.name)
+        } else {
+            format!("< {} >", self.groups[self.target_index - 1].1)
+        }
+    }
+
+    fn confirm(&mut self) -> std::io::Result<()> {
+        let servers: Vec<SSServer> = self
+            .parsed
+            .iter()
+            .filter_map(|(_, result)| result.as_ref().ok().cloned())
+            .collect();
+        if servers.is_empty() {
+            MessageBoxLayer::new("Error", "no valid ss:// link")
+                .red()
+                .on_gray()
+                .show()?;
+            return Ok(());
+        }
+        let target = if self.target_index == 0 {
+            if self.name.trim().is_empty() {
+                MessageBoxLayer::new("Error", "group name is empty")
+                    .red()
+                    .on_gray()
+                    .show()?;
+                return Ok(());
+            }
+            PasteTarget::NewGroup(self.name.trim().to_string())
+        } else {
+            PasteTarget::Group(self.groups[self.target_index - 1].0)
+        };
+        self.result = Some((target, servers));
+        self.exit = true;
+        Ok(())
+    }
+}
+
+impl Layer for SSServerPasteLayer {
+    fn before_show(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn view(&mut self, frame: &mut ratatui::Frame) {
+        let [title_layout, text_layout, preview_layout, target_layout, name_layout, footer_layout] =
+            Layout::vertical([
+                Constraint::Length(3),
+                Constraint::Length(8),
+                Constraint::Min(5),
+                Constraint::Length(1),
+                Constraint::Length(3),
+                Constraint::Length(1),
+            ])
+            .flex(Flex::Legacy)
+            .areas(frame.area());
+
+        let title = " Paste ss:// Links ";
+        let [title_layout] = Layout::horizontal([Constraint::Length(title.len() as u16 + 2)])
+            .flex(Flex::Center)
+            .areas(title_layout);
+        let title = Paragraph::new(title).centered().block(Block::bordered());
+        frame.render_widget(title, title_layout);
+
+        let mut text = Paragraph::new(self.text.as_str())
+            .block(Block::bordered().title("Links (paste or type)"))
+            .wrap(Wrap { trim: true });
+        if let State::Links = self.state {
+            text = text.green();
+        }
+        frame.render_widget(text, text_layout);
+
+        let header = Row::new(["Line", "Name", "Server", "Port", "Method", "Plugin"]).white();
+        let rows = self.parsed.iter().enumerate().map(|(i, (line, result))| {
+            let line = line.to_string();
+            match result {
+                Ok(server) => Row::new([
+                    line,
+                    server.remarks.clone(),
+                    server.server.clone(),
+                    server.server_port.to_string(),
+                    server.method.clone(),
+                    server.plugin.clone().unwrap_or_default(),
+                ])
+                .set_style(self.row_styles[i % 2]),
+                Err(err) => Row::new([line, err.to_string()]).red().on_black(),
+            }
+        });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(5),
+                Constraint::Fill(2),
+                Constraint::Fill(2),
+                Constraint::Length(6),
+                Constraint::Fill(1),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(header)
+        .block(Block::bordered().title(format!(
+            "Preview ({} ok, {} failed)",
+            self.parsed.iter().filter(|(_, r)| r.is_ok()).count(),
+            self.parsed.iter().filter(|(_, r)| r.is_err()).count(),
+        )));
+        frame.render_widget(table, preview_layout);
+
+        let target = Paragraph::new(Line::from(vec![
+            "Append to (← →): ".into(),
+            self.target_str().yellow(),
+        ]));
+        frame.render_widget(target, target_layout);
+
+        let mut name =
+            Paragraph::new(self.name.as_str()).block(Block::bordered().title("New Group Name"));
+        if let State::Name = self.state {
+            name = name.green();
+        }
+        frame.render_widget(name, name_layout);
+
+        let footer = Paragraph::new(
+            "Next (Tab) | Target (← →) | Clear (Del) | Confirm (Enter) | Exit (Esc)",
+        )
+        .centered();
+        frame.render_widget(footer, footer_layout);
+    }
+
+    fn update(&mut self, event: Option<Event>) -> std::io::Result<()> {
+        match event {
+            Some(Event::Paste(text)) => {
+                if let State::Name = self.state {
+                    self.name.push_str(text.lines().next().unwrap_or_default());
+                } else {
+                    if !self.text.is_empty() && !self.text.ends_with('\n') {
+                        self.text.push('\n');
+                    }
+                    self.text.push_str(&text);
+                    self.parse();
+                }
+            }
+            Some(Event::Key(key_event)) if key_event.kind == KeyEventKind::Press => {
+                match key_event.code {
+                    KeyCode::Esc => self.exit = true,
+                    KeyCode::Tab => {
+                        self.state = match self.state {
+                            State::Links => State::Name,
+                            State::Name => State::Links,
+                        };
+                    }
+                    KeyCode::Left => {
+                        self.target_index =
+                            (self.target_index + self.groups.len()) % (self.groups.len() + 1);
+                    }
+                    KeyCode::Right => {
+                        self.target_index = (self.target_index + 1) % (self.groups.len() + 1);
+                    }
+                    KeyCode::Delete => match self.state {
+                        State::Links => {
+                            self.text.clear();
+                            self.parse();
+                        }
+                        State::Name => self.name.clear(),
+                    },
+                    KeyCode::Backspace => match self.state {
+                        State::Links => {
+                            self.text.pop();
+                            self.parse();
+                        }
+                        State::Name => {
+                            self.name.pop();
+                        }
+                    },
+                    KeyCode::Char(c) => match self.state {
+                        State::Links => {
+                            self.text.push(c);
+                            self.parse();
+                        }
+                        State::Name => self.name.push(c),
+                    },
+                    KeyCode::Enter => self.confirm()?,
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn close(&mut self) {
+        self.exit = true;
+    }
+
+    fn is_exit(&self) -> bool {
+        self.exit
+    }
+}
@@ -1 +1,3 @@
+mod qrcode;
 
+pub use qrcode::QrCodeWidget;
@@ -0,0 +1,55 @@
+use qrcode::{Color as QrColor, QrCode};
+use ratatui::{buffer::Buffer, layout::Rect, style::Color, widgets::Widget};
+
+/// Renders a QR code with Unicode half blocks, two modules per cell.
+pub struct QrCodeWidget {
+    code: QrCode,
+}
+
+impl QrCodeWidget {
+    /// Modules of light border around the code, scanners need at least some.
+    const QUIET_ZONE: usize = 2;
+
+    pub fn new(code: QrCode) -> Self {
+        Self { code }
+    }
+
+    /// Size in cells, including the quiet zone.
+    pub fn size(&self) -> (u16, u16) {
+        let modules = self.code.width() + Self::QUIET_ZONE * 2;
+        (modules as u16, modules.div_ceil(2) as u16)
+    }
+
+    fn is_dark(&self, x: usize, y: usize) -> bool {
+        let width = self.code.width();
+        let (Some(x), Some(y)) = (
+            x.checked_sub(Self::QUIET_ZONE),
+            y.checked_sub(Self::QUIET_ZONE),
+        ) else {
+            return false;
+        };
+        x < width && y < width && self.code[(x, y)] == QrColor::Dark
+    }
+}
+
+impl Widget for QrCodeWidget {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let (width, height) = self.size();
+        for row in 0..height.min(area.height) {
+            for col in 0..width.min(area.width) {
+                let x = col as usize;
+                let y = row as usize * 2;
+                let symbol = match (self.is_dark(x, y), self.is_dark(x, y + 1)) {
+                    (true, true) => "█",
+                    (true, false) => "▀",
+                    (false, true) => "▄",
+                    (false, false) => " ",
+                };
+                buf[(area.x + col, area.y + row)]
+                    .set_symbol(symbol)
+                    .set_fg(Color::Black)
+                    .set_bg(Color::White);
+            }
+        }
+    }
+}